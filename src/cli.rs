@@ -0,0 +1,531 @@
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use anyhow::{Context, Result};
+
+use crate::models::{Config, CsvFormat, DateRange};
+use crate::export::*;
+use crate::render::*;
+use crate::reports::MonthlyReport;
+use crate::timesheet::timesheet_pdf;
+use crate::xlsx::range_xlsx;
+use crate::invoice::*;
+use crate::import::*;
+use crate::trackers::*;
+use crate::ics::*;
+use crate::timeclock::*;
+use crate::org::*;
+use crate::undo::{redo_menu, undo_menu};
+use crate::history::print_history;
+use crate::locks::print_locks;
+use crate::seals::print_verification;
+use crate::git::{git_init, sync, print_sync};
+use crate::query::*;
+use crate::utils::{edit_project_entry, print_project_entry, print_workday};
+use crate::search::*;
+
+/// Project Time Tracker. Without a command the interactive menu starts
+#[derive(Debug, Parser)]
+#[command(name = "ptt", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Filter and group entries, e.g. 'project:NLS tag:meeting since:2025-10-01 group:week'
+    Query {
+        /// Query terms: project, tag, since, until, month, year, is, group
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Print the matching entries instead of totals
+        #[arg(long)]
+        entries: bool
+    },
+    /// Full-text search in the activities: words, "phrases" or /regexes/
+    Search {
+        /// Search terms, all of them must match. A quoted argument with spaces is a phrase
+        #[arg(required = true)]
+        terms: Vec<String>,
+        /// Restrict the search with query terms, e.g. 'project:NLS since:2025-10-01'
+        #[arg(long)]
+        filter: Option<String>,
+        /// Choose one of the hits and edit it
+        #[arg(long)]
+        edit: bool
+    },
+    /// Render the monthly report as text, Markdown or HTML
+    Report {
+        /// Month of the report (YYYY-MM), defaults to the current month
+        #[arg(long, value_parser = parse_month)]
+        month: Option<(i32, u32)>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Create the monthly timesheet for signature as PDF
+    Timesheet {
+        /// Month of the timesheet (YYYY-MM), defaults to the current month
+        #[arg(long, value_parser = parse_month)]
+        month: Option<(i32, u32)>,
+        /// Customer shown in the header, defaults to the settings
+        #[arg(long)]
+        customer: Option<String>,
+        /// Add the change history of the days on an extra page
+        #[arg(long)]
+        history: bool,
+        /// Output file, defaults to timesheet-YYYY-MM.pdf
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Submit a month (2025-11) or week (2025-W45) and lock its workdays against changes
+    Lock {
+        period: String
+    },
+    /// Open a locked period again. The reason is kept in the log
+    Unlock {
+        period: String,
+        /// Why the period is changed after submission
+        #[arg(long)]
+        reason: String
+    },
+    /// List the locked periods and the log of locks and unlocks
+    Locks,
+    /// Recompute the seals of the locked periods and show which days changed after sealing
+    Verify,
+    /// Show how the workday of a date and its entries were changed
+    History {
+        /// Date of the workday (YYYY-MM-DD)
+        date: NaiveDate
+    },
+    /// Invoice the unbilled hours of a client and mark them as billed
+    Invoice {
+        /// Client of the projects to bill
+        #[arg(long)]
+        client: String,
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = InvoiceFormat::Pdf)]
+        format: InvoiceFormat,
+        /// Only print the line items, without creating the invoice
+        #[arg(long)]
+        dry_run: bool,
+        /// Output file, defaults to the invoice number
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Show or delete a workday by its id
+    #[command(subcommand)]
+    Record(RecordCommand),
+    /// Show, edit or delete a project entry by its id
+    #[command(subcommand)]
+    Entry(EntryCommand),
+    /// Export records and reports
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Import records from other files
+    #[command(subcommand)]
+    Import(ImportCommand),
+    /// Write the journal into the data files and move it to the journal archive
+    Compact,
+    /// Pull and push the data from and to the git remote, see the git mode in the README
+    Sync {
+        /// Make the data directory a git repository that commits every save
+        #[arg(long)]
+        init: bool,
+        /// URL or path of the remote repository, remembered for this machine in .git/config
+        #[arg(long)]
+        remote: Option<String>
+    },
+    /// Revert the last save
+    Undo,
+    /// Repeat the last undone save
+    Redo
+}
+
+/// Parses a month like 2025-11
+pub fn parse_month(s: &str) -> Result<(i32, u32), String> {
+    let invalid = || format!("'{}' is not a month like 2025-11", s);
+    let (year, month) = s.split_once('-').ok_or_else(invalid)?;
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    if !(1..=12).contains(&month) {
+        return Err(invalid());
+    }
+    Ok((year, month))
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RecordCommand {
+    /// Show a workday and the ids of its entries
+    Show {
+        id: u64
+    },
+    /// Delete a workday with all its entries, after a confirmation
+    Delete {
+        id: u64,
+        /// Why the workday is deleted, kept in the history
+        #[arg(long)]
+        reason: Option<String>
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EntryCommand {
+    /// Show a project entry and its workday
+    Show {
+        id: u64
+    },
+    /// Change a project entry. Without options, it is edited interactively
+    Edit {
+        id: u64,
+        #[arg(long)]
+        hours: Option<f64>,
+        #[arg(long)]
+        activity: Option<String>,
+        /// Replaces the tags, comma separated
+        #[arg(long, value_delimiter = ',')]
+        tags: Option<Vec<String>>,
+        /// Why the entry is changed, kept in the history
+        #[arg(long)]
+        reason: Option<String>
+    },
+    /// Delete a project entry
+    Delete {
+        id: u64,
+        /// Why the entry is deleted, kept in the history
+        #[arg(long)]
+        reason: Option<String>
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportCommand {
+    /// Export entries or reports as CSV
+    Csv {
+        /// What to export
+        #[arg(long, value_enum, default_value_t = CsvReport::Entries)]
+        report: CsvReport,
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Field delimiter, defaults to the settings
+        #[arg(long)]
+        delimiter: Option<char>,
+        /// Decimal separator, defaults to the settings
+        #[arg(long)]
+        decimal: Option<char>,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Export workdays, entries and a project by day pivot as Excel workbook
+    Xlsx {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output file, defaults to export.xlsx
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Export workdays and entries as iCalendar file
+    Ics {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Export entries in the CSV layout of the Clockify import
+    Clockify {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Export entries as JSON for timew import
+    Timew {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Export entries as ledger timeclock file
+    Timeclock {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    },
+    /// Export entries as Org file with a heading per day and entry
+    Org {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Output file, stdout if missing
+        #[arg(long, short)]
+        out: Option<PathBuf>
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportCommand {
+    /// Import workdays and project entries from CSV. All rows are checked first, nothing is imported on errors
+    Csv {
+        /// CSV file with a header row
+        file: PathBuf,
+        /// Column of a field as field=column, e.g. --map date=Datum. Defaults to the entries export columns
+        #[arg(long = "map")]
+        mappings: Vec<String>,
+        /// Field delimiter, defaults to the settings
+        #[arg(long)]
+        delimiter: Option<char>,
+        /// Decimal separator, defaults to the settings
+        #[arg(long)]
+        decimal: Option<char>,
+        /// Only show what would be imported
+        #[arg(long)]
+        dry_run: bool
+    },
+    /// Import a Toggl Track detailed report (CSV or JSON)
+    Toggl(TrackerArgs),
+    /// Import a Clockify detailed report (CSV or JSON)
+    Clockify(TrackerArgs),
+    /// Import the output of timew export. The first tag with a project code (or the first tag) is the project
+    Timew(TrackerArgs),
+    /// Import a ledger timeclock file. The account is the project, its parent account the client
+    Timeclock(TrackerArgs),
+    /// Import the CLOCK lines of an Org file. The PROJECT property or the first word of the heading is the project
+    Org(TrackerArgs),
+    /// Suggest project entries for the meetings of a calendar (.ics) and ask for each one
+    Ics {
+        /// iCalendar file
+        file: PathBuf,
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Only list the suggestions
+        #[arg(long)]
+        dry_run: bool
+    }
+}
+
+/// Options of the Toggl Track and Clockify imports
+#[derive(Debug, Args)]
+pub struct TrackerArgs {
+    /// Exported file, JSON if it ends with .json, CSV otherwise
+    pub file: PathBuf,
+    /// Project code of a project (or client) name as NAME=CODE, e.g. --map 'Website Relaunch=WEB'
+    #[arg(long = "map")]
+    pub mappings: Vec<String>,
+    /// Map clients instead of projects to project codes
+    #[arg(long)]
+    pub by_client: bool,
+    /// Create projects for names without project code
+    #[arg(long)]
+    pub create: bool,
+    /// Only show what would be imported
+    #[arg(long)]
+    pub dry_run: bool
+}
+
+impl TrackerArgs {
+    pub fn options(&self) -> Result<TrackerImport> {
+        let mut mapping = std::collections::BTreeMap::new();
+        for spec in &self.mappings {
+            let (name, code) = spec
+                .rsplit_once('=')
+                .with_context(|| format!("'{}' is not a mapping like 'Website Relaunch=WEB'", spec))?;
+            mapping.insert(name.trim().to_string(), code.trim().to_string());
+        }
+        Ok(TrackerImport { mapping, by_client: self.by_client, create: self.create })
+    }
+}
+
+/// Date range given on the command line, either a month or from/to. Open ends are unbounded
+#[derive(Debug, Args)]
+pub struct RangeArgs {
+    /// Month (YYYY-MM) instead of --from/--to
+    #[arg(long, value_parser = parse_month, conflicts_with_all = ["from", "to"])]
+    pub month: Option<(i32, u32)>,
+    /// First day (YYYY-MM-DD)
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+    /// Last day (YYYY-MM-DD)
+    #[arg(long)]
+    pub to: Option<NaiveDate>
+}
+
+impl RangeArgs {
+    pub fn range(&self) -> Result<DateRange> {
+        match self.month {
+            Some((year, month)) => DateRange::month(year, month).context("Invalid month"),
+            None => Ok(DateRange::new(
+                self.from.unwrap_or(NaiveDate::MIN),
+                self.to.unwrap_or(NaiveDate::MAX),
+            )),
+        }
+    }
+}
+
+/// Executes a command given on the command line
+pub fn execute(config: &mut Config, command: Command) -> Result<()> {
+    match command {
+        Command::Compact => {
+            let events = config.journal.events;
+            config.compact()?;
+            println!("Compacted {} journal events into the data files", events);
+        },
+        Command::Sync { init, remote } => {
+            if init {
+                git_init(config, remote)?;
+                println!("Tracking the data in {} with git", config.data_dir().display());
+                if config.git_remote().is_none() {
+                    return Ok(());
+                }
+            } else if let Some(remote) = &remote {
+                config.set_git_remote(Some(remote))?;
+            }
+            let pulled = sync(config)?;
+            print_sync(config, &pulled);
+        },
+        Command::Undo => undo_menu(config)?,
+        Command::Redo => redo_menu(config)?,
+        Command::Query { query, entries } => {
+            let query: Query = query.join(" ").parse()?;
+            if entries {
+                print_query_entries(&query, config);
+            } else {
+                print_query_totals(&query, config);
+            }
+        },
+        Command::Search { terms, filter, edit } => {
+            let search = Search::from_args(&terms)?;
+            let query: Query = filter.as_deref().unwrap_or_default().parse()?;
+            search_and_edit(config, &search, &query, edit)?;
+        },
+        Command::Report { month, format, out } => {
+            let (year, month) = month.unwrap_or_else(current_month);
+            let report = MonthlyReport::build(config, year, month)?;
+            write_output(&render_monthly(&report, format), out.as_deref())?;
+        },
+        Command::Timesheet { month, customer, history, out } => {
+            let (year, month) = month.unwrap_or_else(current_month);
+            let report = MonthlyReport::build(config, year, month)?;
+            let mut header = config.settings.timesheet.clone();
+            if let Some(customer) = customer {
+                header.customer = customer;
+            }
+            let out = out.unwrap_or_else(|| PathBuf::from(format!("timesheet-{}-{:02}.pdf", year, month)));
+            write_bytes(&timesheet_pdf(&report, &header, history), &out)?;
+        },
+        Command::History { date } => print_history(config, date)?,
+        Command::Record(RecordCommand::Show { id }) => {
+            let record = config.find_record(id).with_context(|| format!("No time record with id #{}", id))?;
+            print_workday(record);
+        },
+        Command::Record(RecordCommand::Delete { id, reason }) => {
+            config.reason = reason;
+            config.delete_time_record(id)?;
+        },
+        Command::Entry(EntryCommand::Show { id }) => {
+            let (record, entry) = config.find_entry(id).with_context(|| format!("No project entry with id #{}", id))?;
+            println!("Workday #{} on {}", record.id, record.date);
+            print_project_entry(entry);
+        },
+        Command::Entry(EntryCommand::Edit { id, hours, activity, tags, reason }) => {
+            config.reason = reason;
+            if hours.is_none() && activity.is_none() && tags.is_none() {
+                edit_project_entry(config, id)?;
+            } else {
+                config.update_project_entry(id, hours, activity, tags)?;
+            }
+        },
+        Command::Entry(EntryCommand::Delete { id, reason }) => {
+            config.reason = reason;
+            config.delete_project_entry(id)?;
+        },
+        Command::Lock { period } => {
+            let lock = config.lock_period(&period)?;
+            println!("{} is locked and sealed", lock);
+        },
+        Command::Unlock { period, reason } => {
+            config.unlock_period(&period, &reason)?;
+            println!("{} is unlocked", period);
+        },
+        Command::Locks => print_locks(config),
+        Command::Verify => print_verification(config)?,
+        Command::Export(ExportCommand::Csv { report, range, delimiter, decimal, out }) => {
+            let format = CsvFormat {
+                delimiter: delimiter.unwrap_or(config.settings.csv.delimiter),
+                decimal_separator: decimal.unwrap_or(config.settings.csv.decimal_separator),
+            };
+            let contents = report_csv(config, report, range.range()?, &format)?;
+            write_output(&contents, out.as_deref())?;
+        },
+        Command::Export(ExportCommand::Xlsx { range, out }) => {
+            let out = out.unwrap_or_else(|| PathBuf::from("export.xlsx"));
+            write_bytes(&range_xlsx(config, range.range()?)?, &out)?;
+        },
+        Command::Invoice { client, range, format, dry_run, out } => {
+            let invoice = Invoice::collect(config, &client, range.range()?, Local::now().date_naive())?;
+            print_invoice(&invoice);
+            if dry_run {
+                return Ok(());
+            }
+            let out = out.unwrap_or_else(|| PathBuf::from(format!("{}.{}", invoice.number, format.extension())));
+            issue_invoice(config, &invoice, format, &out)?;
+        },
+        Command::Import(ImportCommand::Csv { file, mappings, delimiter, decimal, dry_run }) => {
+            let mut mapping = ColumnMapping::default();
+            for spec in &mappings {
+                mapping.set(spec)?;
+            }
+            let format = CsvFormat {
+                delimiter: delimiter.unwrap_or(config.settings.csv.delimiter),
+                decimal_separator: decimal.unwrap_or(config.settings.csv.decimal_separator),
+            };
+            import_csv(config, &file, &mapping, &format, dry_run)?;
+        },
+        Command::Import(ImportCommand::Toggl(args) | ImportCommand::Clockify(args)) => {
+            import_tracker(config, &args.file, &args.options()?, args.dry_run)?;
+        },
+        Command::Import(ImportCommand::Timew(args)) => {
+            let options = args.options()?;
+            let entries = read_timew_file(config, &options, &args.file)?;
+            import_entries(config, &entries, &options, args.dry_run)?;
+        },
+        Command::Import(ImportCommand::Timeclock(args)) => {
+            import_entries(config, &read_timeclock_file(&args.file)?, &args.options()?, args.dry_run)?;
+        },
+        Command::Import(ImportCommand::Org(args)) => {
+            let options = args.options()?;
+            let entries = read_org_file(config, &options, &args.file)?;
+            import_entries(config, &entries, &options, args.dry_run)?;
+        },
+        Command::Import(ImportCommand::Ics { file, range, dry_run }) => {
+            import_ics(config, &file, range.range()?, dry_run)?;
+        },
+        Command::Export(ExportCommand::Ics { range, out }) => {
+            write_output(&range_ics(config, range.range()?)?, out.as_deref())?;
+        },
+        Command::Export(ExportCommand::Clockify { range, out }) => {
+            write_output(&clockify_csv(config, range.range()?)?, out.as_deref())?;
+        },
+        Command::Export(ExportCommand::Timew { range, out }) => {
+            write_output(&timew_json(config, range.range()?)?, out.as_deref())?;
+        },
+        Command::Export(ExportCommand::Timeclock { range, out }) => {
+            write_output(&timeclock(config, range.range()?), out.as_deref())?;
+        },
+        Command::Export(ExportCommand::Org { range, out }) => {
+            write_output(&org_file(config, range.range()?), out.as_deref())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use inquire::{Select, Text};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::query::Query;
+use crate::reports::{choose_date_range, project_totals};
+
+/// Which data a CSV export contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CsvReport {
+    /// One row per project entry, with the workday data of its day
+    Entries,
+    /// Hours per month and project
+    Monthly,
+    /// Totals per project over the range
+    Totals
+}
+
+impl std::fmt::Display for CsvReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvReport::Entries => write!(f, "Entries"),
+            CsvReport::Monthly => write!(f, "Monthly Totals"),
+            CsvReport::Totals => write!(f, "Project Totals"),
+        }
+    }
+}
+
+/// Creates a CSV writer for the configured format
+fn csv_writer(format: &CsvFormat) -> Result<csv::Writer<Vec<u8>>> {
+    if !format.delimiter.is_ascii() {
+        bail!("The CSV delimiter must be an ASCII character, got '{}'", format.delimiter);
+    }
+    Ok(csv::WriterBuilder::new()
+        .delimiter(format.delimiter as u8)
+        .from_writer(Vec::new()))
+}
+
+fn finish(writer: csv::Writer<Vec<u8>>) -> Result<String> {
+    let bytes = writer.into_inner().context("Failed to write CSV")?;
+    String::from_utf8(bytes).context("CSV is not valid UTF-8")
+}
+
+/// Raw entries of a date range. Days without entries get a single row without project
+pub fn entries_csv(config: &Config, range: DateRange, format: &CsvFormat) -> Result<String> {
+    let mut writer = csv_writer(format)?;
+    writer.write_record(["date", "start", "end", "pause", "net_hours", "entry_id", "project", "hours", "activity", "tags"])?;
+
+    let mut records: Vec<&TimeRecord> = config.time_records.iter().filter(|r| range.contains(r.date)).collect();
+    records.sort_by_key(|r| r.date);
+
+    for record in records {
+        let day = [
+            record.date.to_string(),
+            record.start_time.format("%H:%M").to_string(),
+            record.end_time.format("%H:%M").to_string(),
+            format.number(record.pause_minutes),
+            format.number(record.get_net_hours()),
+        ];
+
+        if record.project_entries.is_empty() {
+            writer.write_record(day.iter().map(String::as_str).chain(["", "", "", "", ""]))?;
+        }
+        for entry in &record.project_entries {
+            let entry_fields = [
+                entry.id.to_string(),
+                entry.project_name.code.clone(),
+                format.number(entry.hours),
+                entry.activity.clone(),
+                entry.tags.join(" "),
+            ];
+            writer.write_record(day.iter().chain(entry_fields.iter()))?;
+        }
+    }
+    finish(writer)
+}
+
+/// Hours per month and project of a date range
+pub fn monthly_csv(config: &Config, range: DateRange, format: &CsvFormat) -> Result<String> {
+    let mut totals: BTreeMap<(String, String), f64> = BTreeMap::new();
+    for hit in Query::for_range(range).entries(config) {
+        let key = (hit.record.date.format("%Y-%m").to_string(), hit.entry.project_name.code.clone());
+        *totals.entry(key).or_insert(0.0) += hit.entry.hours;
+    }
+
+    let mut writer = csv_writer(format)?;
+    writer.write_record(["month", "project", "internal", "hours"])?;
+    for ((month, project), hours) in totals {
+        let internal = if config.is_internal(&project) { "yes" } else { "no" };
+        writer.write_record([month, project, internal.to_string(), format.number(hours)])?;
+    }
+    finish(writer)
+}
+
+/// Totals per project of a date range
+pub fn totals_csv(config: &Config, range: DateRange, format: &CsvFormat) -> Result<String> {
+    let mut writer = csv_writer(format)?;
+    writer.write_record(["project", "internal", "hours", "entries", "first", "last", "share_percent"])?;
+    for t in project_totals(config, range) {
+        writer.write_record([
+            t.code,
+            String::from(if t.internal { "yes" } else { "no" }),
+            format.number(t.hours),
+            t.entries.to_string(),
+            t.first.to_string(),
+            t.last.to_string(),
+            format.number(t.share * 100.0),
+        ])?;
+    }
+    finish(writer)
+}
+
+/// Builds the CSV of a report
+pub fn report_csv(config: &Config, report: CsvReport, range: DateRange, format: &CsvFormat) -> Result<String> {
+    match report {
+        CsvReport::Entries => entries_csv(config, range, format),
+        CsvReport::Monthly => monthly_csv(config, range, format),
+        CsvReport::Totals => totals_csv(config, range, format),
+    }
+}
+
+/// Writes an export to a file, or to stdout without a path
+pub fn write_output(contents: &str, out: Option<&Path>) -> Result<()> {
+    match out {
+        Some(path) => {
+            fs::write(path, contents).with_context(|| format!("Failed to write {:#?}", path))?;
+            println!("Exported to {}", path.display());
+        },
+        None => print!("{}", contents),
+    }
+    Ok(())
+}
+
+/// Writes a binary export like PDF or XLSX to a file
+pub fn write_bytes(contents: &[u8], path: &Path) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {:#?}", path))?;
+    println!("Exported to {}", path.display());
+    Ok(())
+}
+
+/// Menu entry to export a report as CSV file
+pub fn export_csv_menu(config: &Config) -> Result<()> {
+
+    let reports = vec![CsvReport::Entries, CsvReport::Monthly, CsvReport::Totals];
+    let Some(report) = Select::new("What would you like to export?", reports).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let Some(range) = choose_date_range(config)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let Some(path) = Text::new("File name:")
+        .with_default("export.csv")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let contents = report_csv(config, report, range, &config.settings.csv)?;
+    write_output(&contents, Some(Path::new(&path)))
+}
+
+/// Lets the user choose delimiter and decimal separator of CSV exports
+pub fn choose_csv_format(config: &mut Config) -> Result<()> {
+
+    println!("Current format: delimiter '{}', decimal separator '{}'",
+        config.settings.csv.delimiter, config.settings.csv.decimal_separator);
+
+    let delimiters = vec!["; (German Excel)", ", (Standard)", "Tab"];
+    let Some(delimiter) = Select::new("Delimiter:", delimiters).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let decimals = vec![", (German Excel)", ". (Standard)"];
+    let Some(decimal) = Select::new("Decimal separator:", decimals).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    config.settings.csv = CsvFormat {
+        delimiter: match delimiter {
+            "Tab" => '\t',
+            other => other.chars().next().unwrap_or(','),
+        },
+        decimal_separator: decimal.chars().next().unwrap_or('.'),
+    };
+    config.save()?;
+    Ok(())
+}
@@ -0,0 +1,383 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use inquire::{Confirm, Select, Text};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::journal::{Change, diff, merge, Snapshot};
+use crate::locks::{Locks, LOCKS_FILE, changed_dates};
+use crate::seals::{Seals, SEALS_FILE};
+use crate::history::{Tombstones, TOMBSTONES_FILE};
+
+/// Branch the data is committed to, here and on the remote
+pub const BRANCH: &str = "main";
+
+/// Files of the data directory that aren't committed: they are local to one machine
+pub const GITIGNORE: &str = "journal.jsonl\njournal.archive.jsonl\nundo.json\n.ptt.lock\n";
+
+/// Runs git in a directory
+/// Returns:
+///     What git printed, error with git's message if it failed
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git. Is it installed?")?;
+    if !output.status.success() {
+        bail!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commit message for the changes of a save: a summary line and one line per change
+pub fn commit_message(changes: &[Change]) -> String {
+    let shown: Vec<&Change> = changes.iter().filter(|c| c.is_shown()).collect();
+    let changes: Vec<&Change> = if shown.is_empty() { changes.iter().collect() } else { shown };
+    let mut summary: Vec<String> = changes.iter().take(3).map(|c| c.to_string()).collect();
+    if changes.len() > 3 {
+        summary.push(format!("{} more", changes.len() - 3));
+    }
+    let mut message = summary.join(", ");
+    if changes.len() > 1 {
+        message.push('\n');
+        for change in changes {
+            message.push_str(&format!("\n- {}", change));
+        }
+    }
+    message
+}
+
+/// Name of the git remote ptt syncs with. It is kept in .git/config, so each machine has its own
+const REMOTE: &str = "origin";
+
+impl Config {
+    /// URL or path `ptt sync` pulls from and pushes to, None if this machine has none
+    pub fn git_remote(&self) -> Option<String> {
+        let dir = self.data_dir();
+        if !dir.join(".git").exists() {
+            return None;
+        }
+        git(&dir, &["remote", "get-url", REMOTE]).ok()
+    }
+
+    /// Sets the remote of this machine, None removes it
+    pub fn set_git_remote(&self, remote: Option<&str>) -> Result<()> {
+        let dir = self.data_dir();
+        if !dir.join(".git").exists() {
+            bail!("The data isn't tracked with git yet. Run `ptt sync --init` first");
+        }
+        if self.git_remote().is_some() {
+            git(&dir, &["remote", "remove", REMOTE])?;
+        }
+        if let Some(remote) = remote {
+            git(&dir, &["remote", "add", REMOTE, remote])?;
+        }
+        Ok(())
+    }
+
+    /// Directory of the data files and the repository
+    pub fn data_dir(&self) -> PathBuf {
+        self.t_path.parent().map(Path::to_path_buf).unwrap_or_default()
+    }
+
+    /// Names of the committed files in the data directory that exist
+    fn tracked_files(&self) -> Vec<String> {
+        let dir = self.data_dir();
+        let mut files = vec![String::from(".gitignore")];
+        for path in [&self.t_path, &self.p_path, &self.s_path, &dir.join(LOCKS_FILE), &dir.join(SEALS_FILE), &dir.join(TOMBSTONES_FILE)] {
+            if path.exists()
+                && let Some(name) = path.file_name()
+            {
+                files.push(name.to_string_lossy().into_owned());
+            }
+        }
+        files
+    }
+
+    /// Commits the data files if git is turned on and they changed
+    pub fn git_commit(&self, message: &str) -> Result<()> {
+        if !self.settings.git.enabled {
+            return Ok(());
+        }
+        let dir = self.data_dir();
+        let files = self.tracked_files();
+        let mut args = vec!["add", "--"];
+        args.extend(files.iter().map(String::as_str));
+        git(&dir, &args)?;
+        if git(&dir, &["diff", "--cached", "--quiet"]).is_ok() {
+            return Ok(());
+        }
+        commit(&dir, message)
+    }
+}
+
+/// Arguments giving git an identity if none is configured, so commits and merges work without
+fn identity(dir: &Path) -> Vec<&'static str> {
+    if git(dir, &["config", "user.email"]).is_err() {
+        return vec!["-c", "user.name=ptt", "-c", "user.email=ptt@localhost"];
+    }
+    vec![]
+}
+
+/// Commits what is staged
+fn commit(dir: &Path, message: &str) -> Result<()> {
+    let mut args = identity(dir);
+    args.extend(["commit", "--quiet", "-m", message]);
+    git(dir, &args)?;
+    Ok(())
+}
+
+/// Contents of a file in a commit, None if the commit doesn't have it
+fn file_at(dir: &Path, rev: &str, name: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["show", &format!("{}:{}", rev, name)])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parses a file of a commit, a missing or empty one gives the default
+fn parse<T: serde::de::DeserializeOwned + Default>(contents: Option<String>, name: &str, rev: &str) -> Result<T> {
+    match contents {
+        Some(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to deserialze {} of commit {}", name, rev)),
+        _ => Ok(T::default()),
+    }
+}
+
+/// Data of a commit, missing or empty files give the defaults
+fn snapshot_at(config: &Config, rev: &str) -> Result<Snapshot> {
+    let dir = config.data_dir();
+    let name = |path: &Path| path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let (t_name, p_name, s_name) = (name(&config.t_path), name(&config.p_path), name(&config.s_path));
+    Ok(Snapshot {
+        time_records: parse(file_at(&dir, rev, &t_name), &t_name, rev)?,
+        project_records: parse(file_at(&dir, rev, &p_name), &p_name, rev)?,
+        settings: parse(file_at(&dir, rev, &s_name), &s_name, rev)?,
+    })
+}
+
+/// Merges the fetched commits into the data. The workdays, projects and settings are merged like
+/// concurrent saves, see journal::merge, with the data of the common commit as base. Locks, seals
+/// and the .gitignore are taken from the side that changed them. The result is committed as merge
+/// Returns:
+///     What the remote changed since the common commit
+fn merge_fetched(config: &mut Config, remote: &str) -> Result<Vec<Change>> {
+    let dir = config.data_dir();
+    let head = git(&dir, &["rev-parse", "HEAD"])?;
+    let fetched = git(&dir, &["rev-parse", "FETCH_HEAD"])?;
+    // Repositories started on their own have no common commit
+    let base = git(&dir, &["merge-base", "HEAD", "FETCH_HEAD"]).ok();
+    if base.as_deref() == Some(fetched.as_str()) {
+        return Ok(vec![]);
+    }
+    if base.as_deref() == Some(head.as_str()) {
+        let before = Snapshot::of(config);
+        git(&dir, &["merge", "--quiet", "--ff-only", "FETCH_HEAD"])?;
+        config.merge_from_disk()?;
+        return Ok(diff(&before, &Snapshot::of(config)));
+    }
+
+    let base_snapshot = match &base {
+        Some(base) => snapshot_at(config, base)?,
+        None => Snapshot::default(),
+    };
+    let (ours, theirs) = (snapshot_at(config, &head)?, snapshot_at(config, &fetched)?);
+    let (merged, notes) = merge(&base_snapshot, &ours, &theirs);
+    let mut files = Vec::new();
+    for name in [".gitignore", LOCKS_FILE, SEALS_FILE, TOMBSTONES_FILE] {
+        let at = |rev: Option<&str>| rev.and_then(|rev| file_at(&dir, rev, name));
+        let (base_file, ours, theirs) = (at(base.as_deref()), at(Some(&head)), at(Some(&fetched)));
+        let file = if ours == base_file {
+            theirs
+        } else if theirs == base_file || theirs == ours {
+            ours
+        } else if let (TOMBSTONES_FILE, Some(ours), Some(theirs)) = (name, &ours, &theirs) {
+            // Both deleted workdays, all of them are kept
+            let parse = |contents: &str| serde_json::from_str::<Tombstones>(contents).context("Failed to deserialze deleted workdays");
+            let mut tombstones = parse(ours)?;
+            tombstones.merge(parse(theirs)?);
+            Some(serde_json::to_string_pretty(&tombstones).context("Failed to serialize deleted workdays")?)
+        } else {
+            bail!("{} was changed here and on {} since the last sync. Nothing was changed, merge it with git in {} and sync again", name, remote, dir.display());
+        };
+        files.push((name, file));
+    }
+
+    // Neither side may change a day the other one locked, unless the merge unlocks it
+    let merged_locks: Locks = parse(files.iter().find(|(name, _)| *name == LOCKS_FILE).and_then(|(_, file)| file.clone()), LOCKS_FILE, "the merge")?;
+    let our_locks: Locks = parse(file_at(&dir, &head, LOCKS_FILE), LOCKS_FILE, &head)?;
+    let their_locks: Locks = parse(file_at(&dir, &fetched, LOCKS_FILE), LOCKS_FILE, &fetched)?;
+    let locked = changed_dates(&ours, &merged).into_iter().filter(|d| our_locks.locked(*d).is_some())
+        .chain(changed_dates(&theirs, &merged).into_iter().filter(|d| their_locks.locked(*d).is_some()))
+        .find(|d| merged_locks.locked(*d).is_some());
+    if let Some(date) = locked {
+        bail!("Merging with {} would change {}, which is locked. Nothing was changed, unlock it or merge with git in {} and sync again", remote, date, dir.display());
+    }
+
+    // The merge is written and committed by a copy, so the data stays as it was if that fails
+    let mut merging = config.clone();
+    let mut args = identity(&dir);
+    args.extend(["merge", "--quiet", "--no-commit", "--no-ff", "--allow-unrelated-histories", "-s", "ours", "FETCH_HEAD"]);
+    git(&dir, &args)?;
+    let result = (|| -> Result<()> {
+        for (name, file) in &files {
+            let path = dir.join(name);
+            match file {
+                Some(contents) => fs::write(&path, contents).with_context(|| format!("Failed to write {:#?}", path))?,
+                None if path.exists() => fs::remove_file(&path).with_context(|| format!("Failed to remove {:#?}", path))?,
+                None => {},
+            }
+        }
+        merging.journal.base = merged.clone();
+        let Snapshot { time_records, project_records, settings } = merged;
+        (merging.time_records, merging.project_records, merging.settings) = (time_records, project_records, settings);
+        merging.locks = Locks::open(&merging.t_path)?;
+        merging.seals = Seals::open(&merging.t_path)?;
+        merging.tombstones = Tombstones::open(&merging.t_path)?;
+        merging.compact()?;
+
+        let tracked = merging.tracked_files();
+        let mut args = vec!["add", "--"];
+        args.extend(tracked.iter().map(String::as_str));
+        git(&dir, &args)?;
+        let mut message = format!("Merge the data of {}", remote);
+        if !notes.is_empty() {
+            message.push('\n');
+            for note in &notes {
+                message.push_str(&format!("\n- {}", note));
+            }
+        }
+        commit(&dir, &message)
+    })();
+    if let Err(e) = result {
+        let _ = git(&dir, &["merge", "--abort"]);
+        bail!("{:#}\nNothing was changed. Merge with git in {} and sync again", e, dir.display());
+    }
+    *config = merging;
+    for note in &notes {
+        println!("  {}", note);
+    }
+    Ok(diff(&base_snapshot, &theirs))
+}
+
+/// Whether the remote has the branch already
+fn remote_has_data(dir: &Path, remote: &str) -> Result<bool> {
+    Ok(!git(dir, &["ls-remote", "--heads", remote, BRANCH])?.is_empty())
+}
+
+/// Turns the data directory into a git repository and commits the data. With a remote that
+/// has data already, its data is checked out instead, as long as there is none here
+pub fn git_init(config: &mut Config, remote: Option<String>) -> Result<()> {
+    let dir = config.data_dir();
+    if !dir.join(".git").exists() {
+        git(&dir, &["init", "--quiet"])?;
+        git(&dir, &["symbolic-ref", "HEAD", &format!("refs/heads/{}", BRANCH)])?;
+    }
+    let gitignore = dir.join(".gitignore");
+    let ignored = fs::read_to_string(&gitignore).unwrap_or_default();
+    let missing: String = GITIGNORE.lines().filter(|l| !ignored.lines().any(|i| i == *l)).map(|l| format!("{}\n", l)).collect();
+    if !missing.is_empty() {
+        let separator = if ignored.is_empty() || ignored.ends_with('\n') { "" } else { "\n" };
+        fs::write(&gitignore, format!("{}{}{}", ignored, separator, missing))
+            .with_context(|| format!("Failed to write {:#?}", gitignore))?;
+    }
+
+    if let Some(remote) = &remote {
+        config.set_git_remote(Some(remote))?;
+    }
+    let remote = config.git_remote();
+    let has_commits = git(&dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok();
+    if let Some(remote) = &remote
+        && !has_commits
+        && remote_has_data(&dir, remote)?
+    {
+        if !config.time_records.is_empty() {
+            bail!("{} has ptt data already and so has {}. Start in an empty directory to use the data of {}", remote, dir.display(), remote);
+        }
+        git(&dir, &["fetch", "--quiet", remote, BRANCH])?;
+        git(&dir, &["checkout", "--quiet", "--force", "-B", BRANCH, "FETCH_HEAD"])?;
+        config.merge_from_disk()?;
+        println!("Checked out the data of {}", remote);
+    }
+
+    config.settings.git = GitSettings { enabled: true };
+    config.compact()?;
+    config.git_commit("Track the ptt data with git")?;
+    config.save()
+}
+
+/// Commits, fetches the commits of the remote, merges them into the data and pushes the own ones
+/// Returns:
+///     What changed by pulling
+pub fn sync(config: &mut Config) -> Result<Vec<Change>> {
+    if !config.settings.git.enabled {
+        bail!("The data isn't tracked with git yet. Run `ptt sync --init` first");
+    }
+    let Some(remote) = config.git_remote() else {
+        bail!("No remote to sync with. Set one with `ptt sync --remote <url or path>`");
+    };
+    let dir = config.data_dir();
+    config.compact()?;
+    config.git_commit("Changes before sync")?;
+
+    let mut pulled = Vec::new();
+    if remote_has_data(&dir, &remote)? {
+        git(&dir, &["fetch", "--quiet", &remote, BRANCH])?;
+        pulled = merge_fetched(config, &remote)?;
+    }
+    git(&dir, &["push", "--quiet", &remote, &format!("HEAD:refs/heads/{}", BRANCH)])?;
+    Ok(pulled)
+}
+
+/// Prints the changes a sync pulled. Changes of the order only come from the sorted files and are left out
+pub fn print_sync(config: &Config, pulled: &[Change]) {
+    println!("Synced with {}", config.git_remote().unwrap_or_default());
+    let pulled: Vec<&Change> = pulled.iter()
+        .filter(|c| c.is_shown() && !matches!(c, Change::RecordsOrdered { .. } | Change::EntriesOrdered { .. } | Change::ProjectsOrdered { .. }))
+        .collect();
+    if pulled.is_empty() {
+        println!("Nothing new from the remote");
+    }
+    for change in pulled {
+        println!("  {}", change);
+    }
+}
+
+/// Menu to turn git on or off, set the remote and sync
+pub fn git_menu(config: &mut Config) -> Result<()> {
+
+    loop {
+        let state = if config.settings.git.enabled { "on" } else { "off" };
+        println!("Git: {}, remote: {}", state, config.git_remote().as_deref().unwrap_or("none"));
+        let options = vec!["Track with Git", "Stop Tracking", "Set Remote", "Sync Now", "Back"];
+        match Select::new("Git Sync", options).prompt_skippable()? {
+            Some("Track with Git") => git_init(config, None)?,
+            Some("Stop Tracking") => {
+                let Some(true) = Confirm::new("Stop committing the data? The repository is kept").with_default(false).prompt_skippable()? else { continue };
+                config.settings.git.enabled = false;
+                config.save()?;
+            },
+            Some("Set Remote") => {
+                let Some(remote) = Text::new("URL or path of the remote repository:")
+                    .with_initial_value(config.git_remote().as_deref().unwrap_or_default())
+                    .prompt_skippable()? else {
+                    println!("Operation cancelled. Returning to main...");
+                    continue;
+                };
+                if let Err(e) = config.set_git_remote(Some(remote.trim()).filter(|r| !r.is_empty())) {
+                    println!("{:#}", e);
+                }
+            },
+            Some("Sync Now") => match sync(config) {
+                Ok(pulled) => print_sync(config, &pulled),
+                Err(e) => println!("{:#}", e),
+            },
+            _ => break Ok(()),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, SubsecRound};
+use inquire::Text;
+use serde::{Serialize, Deserialize};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::journal::Snapshot;
+use crate::utils::choose_date;
+
+/// File name of the deleted workdays, next to the data files
+pub const TOMBSTONES_FILE: &str = "deleted.json";
+
+/// Deleted workdays with their history, so the history of a day outlives the day
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Tombstones {
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub records: Vec<TimeRecord>
+}
+
+impl Tombstones {
+    /// Loads the deleted workdays next to the given data file. A missing file has none
+    pub fn open(data_path: &Path) -> Result<Tombstones> {
+        let path = data_path.with_file_name(TOMBSTONES_FILE);
+        let mut tombstones: Tombstones = match fs::read_to_string(&path) {
+            Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to deserialze deleted workdays for path: {:#?}", path))?,
+            _ => Tombstones::default(),
+        };
+        tombstones.path = path;
+        Ok(tombstones)
+    }
+
+    /// Adds the deleted workdays someone else kept, without duplicates
+    pub fn merge(&mut self, other: Tombstones) {
+        for record in other.records {
+            if !self.records.contains(&record) {
+                self.records.push(record);
+            }
+        }
+    }
+
+    /// Writes the deleted workdays, together with the ones another ptt wrote in the meantime
+    pub fn write(&mut self) -> Result<()> {
+        let on_disk = Tombstones::open(&self.path)?;
+        self.merge(on_disk);
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize deleted workdays")?;
+        fs::write(&self.path, json).with_context(|| format!("Failed to write deleted workdays to {:#?}", self.path))
+    }
+}
+
+/// Short form of a workday for the history, e.g. `2025-11-09 08:00-16:30, pause 0.5`
+fn workday_summary(record: &TimeRecord) -> String {
+    format!("{} {}-{}, pause {}", record.date, record.start_time.format("%H:%M"), record.end_time.format("%H:%M"), record.pause_minutes)
+}
+
+/// Short form of an entry for the history, e.g. `INEK 3.5 h: Review`
+fn entry_summary(entry: &ProjectEntry) -> String {
+    format!("{} {} h: {}", entry.project_name.code, entry.hours, entry.activity)
+}
+
+/// Collects the fields whose formatted values differ
+fn changed_fields(fields: Vec<(&str, String, String)>) -> Vec<FieldChange> {
+    fields
+        .into_iter()
+        .filter(|(_, from, to)| from != to)
+        .map(|(field, from, to)| FieldChange { field: field.to_string(), from, to })
+        .collect()
+}
+
+/// Changes of the workday itself and its deleted entries, whose values would be lost otherwise
+fn record_changes(old: &TimeRecord, new: &TimeRecord) -> Vec<FieldChange> {
+    let mut changes = changed_fields(vec![
+        ("date", old.date.to_string(), new.date.to_string()),
+        ("start", old.start_time.format("%H:%M").to_string(), new.start_time.format("%H:%M").to_string()),
+        ("end", old.end_time.format("%H:%M").to_string(), new.end_time.format("%H:%M").to_string()),
+        ("pause", old.pause_minutes.to_string(), new.pause_minutes.to_string()),
+    ]);
+    for entry in &old.project_entries {
+        if !new.project_entries.iter().any(|e| e.id == entry.id) {
+            changes.push(FieldChange { field: format!("entry #{}", entry.id), from: entry_summary(entry), to: String::new() });
+        }
+    }
+    changes
+}
+
+fn entry_changes(old: &ProjectEntry, new: &ProjectEntry) -> Vec<FieldChange> {
+    changed_fields(vec![
+        ("project", old.project_name.code.clone(), new.project_name.code.clone()),
+        ("hours", old.hours.to_string(), new.hours.to_string()),
+        ("activity", old.activity.clone(), new.activity.clone()),
+        ("tags", old.tags.join(", "), new.tags.join(", ")),
+        ("invoice", old.invoice.clone().unwrap_or_default(), new.invoice.clone().unwrap_or_default()),
+    ])
+}
+
+/// Adds a revision with the previous values to every workday and entry changed since the
+/// last save. New workdays and entries start without history, deleted workdays are kept
+/// as tombstones with a revision listing what was deleted
+pub fn record_revisions(base: &Snapshot, config: &mut Config, reason: Option<String>) {
+    let at = Local::now().naive_local().trunc_subsecs(0);
+    let revision = |changes: Vec<FieldChange>| Revision { at, reason: reason.clone(), changes };
+
+    for record in config.time_records.iter_mut() {
+        let Some(old) = base.time_records.iter().find(|r| r.id == record.id) else {
+            continue;
+        };
+        let changes = record_changes(old, record);
+        if !changes.is_empty() {
+            record.history.push(revision(changes));
+        }
+        for entry in record.project_entries.iter_mut() {
+            let Some(old_entry) = old.project_entries.iter().find(|e| e.id == entry.id) else {
+                continue;
+            };
+            let changes = entry_changes(old_entry, entry);
+            if !changes.is_empty() {
+                entry.history.push(revision(changes));
+            }
+        }
+    }
+
+    for old in base.time_records.iter().filter(|b| !config.time_records.iter().any(|r| r.id == b.id)) {
+        let mut changes = vec![FieldChange { field: String::from("workday"), from: workday_summary(old), to: String::new() }];
+        changes.extend(record_changes(old, &TimeRecord { project_entries: vec![], ..old.clone() }));
+        let mut tombstone = old.clone();
+        tombstone.history.push(revision(changes));
+        config.tombstones.records.push(tombstone);
+    }
+}
+
+/// Asks for the reason of a change, which the next save keeps in the history
+pub fn ask_reason(config: &mut Config) -> Result<()> {
+    if config.reason.is_some() {
+        return Ok(());
+    }
+    let reason = Text::new("Reason for the change (optional):")
+        .with_help_message("Kept in the change history, e.g. for audits")
+        .prompt_skippable()?;
+    config.reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    Ok(())
+}
+
+/// Lines of a revision, e.g. `2025-11-10 09:00 hours: 3 -> 3.5 (forgot the review)`
+pub fn revision_lines(revision: &Revision) -> Vec<String> {
+    let reason = revision.reason.as_ref().map(|r| format!(" ({})", r)).unwrap_or_default();
+    revision.changes
+        .iter()
+        .map(|change| format!("{} {}{}", revision.at.format("%Y-%m-%d %H:%M"), change, reason))
+        .collect()
+}
+
+/// History of a workday and its entries, as heading and indented revisions.
+/// Returns:
+///     No lines if nothing was changed
+pub fn history_lines(record: &TimeRecord) -> Vec<String> {
+    let mut lines = Vec::new();
+    if !record.history.is_empty() {
+        lines.push(format!("Workday {}", workday_summary(record)));
+        lines.extend(record.history.iter().flat_map(revision_lines).map(|l| format!("  {}", l)));
+    }
+    for entry in record.project_entries.iter().filter(|e| !e.history.is_empty()) {
+        lines.push(format!("Entry #{} {}", entry.id, entry_summary(entry)));
+        lines.extend(entry.history.iter().flat_map(revision_lines).map(|l| format!("  {}", l)));
+    }
+    lines
+}
+
+/// Prints the change history of a day, including the workdays deleted on it
+pub fn print_history(config: &Config, date: NaiveDate) -> Result<()> {
+    let record = config.time_records.iter().find(|r| r.date == date);
+    let deleted: Vec<&TimeRecord> = config.tombstones.records.iter().filter(|r| r.date == date).collect();
+    if record.is_none() && deleted.is_empty() {
+        bail!("No workday recorded on {}", date);
+    }
+    let mut lines = record.map(history_lines).unwrap_or_default();
+    lines.extend(deleted.into_iter().flat_map(history_lines));
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    if lines.is_empty() {
+        println!("The workday {} and its entries were not changed", date);
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    Ok(())
+}
+
+/// Menu entry to show the change history of a workday
+pub fn history_menu(config: &Config) -> Result<()> {
+
+    let Some((date, _)) = choose_date(config, "Which workday?")? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    print_history(config, date)
+}
@@ -0,0 +1,594 @@
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{BuildHasher, RandomState};
+use std::path::Path;
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use inquire::{CustomType, Select, Text, validator::Validation};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::reports::choose_date_range;
+use crate::export::write_output;
+use crate::utils::{choose_project, choose_tags, find_project};
+
+/// Ending of the UID domains, so they do not clash with events of other programs
+const UID_DOMAIN: &str = "ptt";
+
+impl Config {
+    /// Domain part of the calendar UIDs of this data, e.g. `3f2a9c1e8b7d6054.ptt`. Made up and saved
+    /// on the first export, so UIDs of different data never match and stay the same on every export
+    pub fn uid_domain(&mut self) -> Result<String> {
+        if let Some(domain) = &self.settings.uid_domain {
+            return Ok(domain.clone());
+        }
+        let domain = format!("{:016x}.{}", RandomState::new().hash_one(self.data_dir()), UID_DOMAIN);
+        self.settings.uid_domain = Some(domain.clone());
+        self.persist()?;
+        Ok(domain)
+    }
+}
+
+/// Escapes a TEXT value (RFC 5545, 3.3.11)
+pub fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line into lines of at most 75 octets, continued with a space (RFC 5545, 3.1)
+pub fn fold_line(line: &str) -> String {
+    let mut out = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Formats a local time as UTC date-time like 20251109T070000Z
+pub fn utc_stamp(local: NaiveDateTime) -> String {
+    let utc = Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc).naive_utc())
+        .unwrap_or(local);
+    utc.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// One VEVENT with the given properties
+fn event(out: &mut String, uid: &str, domain: &str, start: NaiveDateTime, end: NaiveDateTime, properties: &[(&str, String)]) {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    out.push_str("BEGIN:VEVENT\r\n");
+    out.push_str(&fold_line(&format!("UID:{}@{}", uid, domain)));
+    out.push_str(&format!("DTSTAMP:{}\r\n", stamp));
+    out.push_str(&format!("DTSTART:{}\r\n", utc_stamp(start)));
+    out.push_str(&format!("DTEND:{}\r\n", utc_stamp(end)));
+    for (name, value) in properties {
+        out.push_str(&fold_line(&format!("{}:{}", name, value)));
+    }
+    out.push_str("END:VEVENT\r\n");
+}
+
+/// Calendar of a date range: each workday is a transparent event for the working time and each entry
+/// an event in its slot of `TimeRecord::entry_slots`. UIDs derive from the ids, which are never
+/// handed out twice, so a calendar importing the file again updates the events instead of duplicating them
+pub fn range_ics(config: &mut Config, range: DateRange) -> Result<String> {
+    let domain = config.uid_domain()?;
+    let mut out = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//ptt//Project Time Tracker//EN\r\nCALSCALE:GREGORIAN\r\n");
+    out.push_str("X-WR-CALNAME:Project Time Tracker\r\n");
+
+    let mut records: Vec<&TimeRecord> = config.time_records.iter().filter(|r| range.contains(r.date)).collect();
+    records.sort_by_key(|r| r.date);
+    for record in records {
+        let entries: Vec<String> = record.project_entries.iter().map(|e| format!("{} {} h: {}", e.project_name.code, e.hours, e.activity)).collect();
+        // Workdays ending before they start run past midnight
+        let mut end = record.date.and_time(record.end_time);
+        if record.end_time < record.start_time {
+            end += TimeDelta::days(1);
+        }
+        event(&mut out, &format!("record-{}", record.id), &domain, record.date.and_time(record.start_time), end, &[
+            ("SUMMARY", ics_escape(&format!("Working time ({} h net)", record.get_net_hours()))),
+            ("DESCRIPTION", ics_escape(&format!("Pause: {} h\n{}", record.pause_minutes, entries.join("\n")))),
+            ("TRANSP", String::from("TRANSPARENT")),
+        ]);
+
+        for (entry, (start, end)) in record.project_entries.iter().zip(record.entry_slots()) {
+            let mut properties = vec![
+                ("SUMMARY", ics_escape(&entry.project_name.code)),
+                ("DESCRIPTION", ics_escape(&entry.activity)),
+            ];
+            if !entry.tags.is_empty() {
+                properties.push(("CATEGORIES", entry.tags.iter().map(|t| ics_escape(t)).collect::<Vec<_>>().join(",")));
+            }
+            event(&mut out, &format!("entry-{}", entry.id), &domain, start, end, &properties);
+        }
+    }
+
+    out.push_str("END:VCALENDAR\r\n");
+    Ok(out)
+}
+
+/// Menu entry to export a date range as calendar file
+pub fn export_ics_menu(config: &mut Config) -> Result<()> {
+
+    let Some(range) = choose_date_range(config)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let Some(path) = Text::new("File name:")
+        .with_default("ptt.ics")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    write_output(&range_ics(config, range)?, Some(Path::new(&path)))
+}
+
+/// A calendar event read from an .ics file. Recurring events are expanded into their occurrences
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    /// Name and address of the organizer, e.g. `Jane Doe <jane@example.com>`
+    pub organizer: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime
+}
+
+/// Reverses `ics_escape`
+fn ics_unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Parameters of a content line as name and value, e.g. `TZID=Europe/Berlin`
+type Params = Vec<(String, String)>;
+
+/// Splits a content line into name, parameters and value. Parameter values may be quoted and contain ':'
+fn split_property(line: &str) -> Option<(String, Params, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c == ':' && !quoted
+    })?.0;
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?.to_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+/// Parses a DATE-TIME value. UTC times are converted to local time, times with TZID are taken as local.
+/// Returns:
+///     None for dates without time, e.g. all-day events
+fn parse_ics_time(value: &str) -> Option<NaiveDateTime> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&utc).with_timezone(&Local).naive_local());
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()
+}
+
+/// Parses a DURATION like PT1H30M or P1D
+fn parse_ics_duration(value: &str) -> Option<TimeDelta> {
+    let mut total = 0;
+    let mut number = String::new();
+    for c in value.trim_start_matches(['+', 'P']).chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {},
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += n * match unit {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(TimeDelta::seconds(total))
+}
+
+/// Recurrence of an event, limited to daily and weekly rules
+#[derive(Debug, Default)]
+struct Recurrence {
+    weekly: bool,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDateTime>,
+    weekdays: Vec<Weekday>
+}
+
+impl Recurrence {
+    /// Parses an RRULE. Returns None for rules other than daily and weekly
+    fn parse(value: &str) -> Option<Recurrence> {
+        let mut rule = Recurrence { interval: 1, ..Recurrence::default() };
+        for (key, val) in value.split(';').filter_map(|p| p.split_once('=')) {
+            match key {
+                "FREQ" => match val {
+                    "DAILY" => rule.weekly = false,
+                    "WEEKLY" => rule.weekly = true,
+                    _ => return None,
+                },
+                "INTERVAL" => rule.interval = val.parse().ok()?,
+                "COUNT" => rule.count = val.parse().ok(),
+                "UNTIL" => rule.until = parse_ics_time(val)
+                    .or_else(|| NaiveDate::parse_from_str(val, "%Y%m%d").ok().map(|d| d.and_time(NaiveTime::MIN + TimeDelta::seconds(86399)))),
+                "BYDAY" => rule.weekdays = val.split(',').filter_map(|d| match d.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+                    "MO" => Some(Weekday::Mon),
+                    "TU" => Some(Weekday::Tue),
+                    "WE" => Some(Weekday::Wed),
+                    "TH" => Some(Weekday::Thu),
+                    "FR" => Some(Weekday::Fri),
+                    "SA" => Some(Weekday::Sat),
+                    "SU" => Some(Weekday::Sun),
+                    _ => None,
+                }).collect(),
+                _ => {},
+            }
+        }
+        Some(rule)
+    }
+
+    /// Starts of the occurrences up to `last` (inclusive)
+    fn starts(&self, first: NaiveDateTime, last: NaiveDate) -> Vec<NaiveDateTime> {
+        let mut starts = Vec::new();
+        let mut day = first.date();
+        let week_start = first.date().week(Weekday::Mon).first_day();
+        while day <= last {
+            let in_pattern = if self.weekly {
+                let weeks = (day.week(Weekday::Mon).first_day() - week_start).num_days() / 7;
+                let weekday_ok = if self.weekdays.is_empty() { day.weekday() == first.weekday() } else { self.weekdays.contains(&day.weekday()) };
+                weeks % self.interval.max(1) as i64 == 0 && weekday_ok
+            } else {
+                (day - first.date()).num_days() % self.interval.max(1) as i64 == 0
+            };
+            if in_pattern {
+                let start = day.and_time(first.time());
+                if self.until.is_some_and(|until| start > until) || self.count.is_some_and(|c| starts.len() as u32 >= c) {
+                    break;
+                }
+                starts.push(start);
+            }
+            day = day.succ_opt().expect("date in range");
+        }
+        starts
+    }
+}
+
+/// Reads the events of a calendar file between two days (inclusive). All-day and cancelled events are left out.
+/// Daily and weekly recurring events are expanded, other recurrences only count with their first occurrence
+pub fn parse_ics(contents: &str, range: DateRange) -> Result<Vec<CalendarEvent>> {
+    let unfolded = contents.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    if !unfolded.trim_start().starts_with("BEGIN:VCALENDAR") {
+        bail!("The file is not an iCalendar file");
+    }
+
+    let mut events = Vec::new();
+    // Occurrences replaced by an event with RECURRENCE-ID
+    let mut overridden: Vec<(String, NaiveDateTime)> = Vec::new();
+    let mut current: Option<HashMap<String, (Params, String)>> = None;
+    let mut exdates: Vec<NaiveDateTime> = Vec::new();
+
+    for line in unfolded.lines() {
+        match line.trim_end() {
+            "BEGIN:VEVENT" => {
+                current = Some(HashMap::new());
+                exdates.clear();
+                continue;
+            },
+            "END:VEVENT" => {},
+            _ => {
+                if let (Some(props), Some((name, params, value))) = (current.as_mut(), split_property(line)) {
+                    if name == "EXDATE" {
+                        exdates.extend(value.split(',').filter_map(parse_ics_time));
+                    } else {
+                        props.insert(name, (params, value.to_string()));
+                    }
+                }
+                continue;
+            },
+        }
+
+        let Some(props) = current.take() else { continue };
+        let get = |name: &str| props.get(name).map(|(_, v)| v.as_str());
+        if get("STATUS") == Some("CANCELLED") {
+            continue;
+        }
+        let Some(start) = get("DTSTART").and_then(parse_ics_time) else {
+            continue;
+        };
+        let duration = match (get("DTEND").and_then(parse_ics_time), get("DURATION").and_then(parse_ics_duration)) {
+            (Some(end), _) => end - start,
+            (None, Some(duration)) => duration,
+            (None, None) => TimeDelta::zero(),
+        };
+        let uid = get("UID").unwrap_or_default().to_string();
+        if let Some(recurrence_id) = get("RECURRENCE-ID").and_then(parse_ics_time) {
+            overridden.push((uid.clone(), recurrence_id));
+        }
+        let organizer = props.get("ORGANIZER").map(|(params, value)| {
+            let address = value.trim_start_matches("mailto:").trim_start_matches("MAILTO:");
+            match params.iter().find(|(k, _)| k == "CN") {
+                Some((_, name)) => format!("{} <{}>", name, address),
+                None => address.to_string(),
+            }
+        }).unwrap_or_default();
+
+        let starts = match get("RRULE").and_then(Recurrence::parse) {
+            Some(rule) => rule.starts(start, range.to.min(Local::now().date_naive())),
+            None => vec![start],
+        };
+        let is_override = get("RECURRENCE-ID").is_some();
+        for start in starts.into_iter().filter(|s| !exdates.contains(s)) {
+            events.push((is_override, CalendarEvent {
+                uid: uid.clone(),
+                summary: ics_unescape(get("SUMMARY").unwrap_or_default()),
+                description: ics_unescape(get("DESCRIPTION").unwrap_or_default()),
+                organizer: organizer.clone(),
+                start,
+                end: start + duration,
+            }));
+        }
+    }
+
+    let mut events: Vec<CalendarEvent> = events
+        .into_iter()
+        // Occurrences replaced by an override event are left out
+        .filter(|(is_override, e)| *is_override || !overridden.iter().any(|(uid, at)| uid == &e.uid && *at == e.start))
+        .map(|(_, e)| e)
+        .filter(|e| range.contains(e.start.date()) && e.end > e.start)
+        .collect();
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
+
+/// Project entry proposed for a calendar event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub date: NaiveDate,
+    pub project: Option<String>,
+    pub hours: f64,
+    pub activity: String
+}
+
+/// Project of an event: the first matching calendar rule, else a project whose code is a word of the summary
+pub fn suggest_project(config: &Config, event: &CalendarEvent) -> Option<String> {
+    let text = format!("{}\n{}", event.summary, event.description);
+    let known = |code: &str| config.project_records.iter().any(|p| p.code == code);
+    if let Some(rule) = config.settings.calendar_rules.iter().find(|r| r.matches(&text, &event.organizer) && known(&r.project)) {
+        return Some(rule.project.clone());
+    }
+    let words: Vec<String> = event.summary
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .collect();
+    config.project_records
+        .iter()
+        .find(|p| words.contains(&p.code.to_lowercase()))
+        .map(|p| p.code.clone())
+}
+
+/// Proposes an entry for an event. The hours are its duration in quarters, but at most the
+/// remaining hours of the day, like `get_activity_hours` allows.
+/// Returns:
+///     Why there is no suggestion, e.g. no workday or no hours left
+pub fn suggest_entry(config: &Config, event: &CalendarEvent) -> Result<Suggestion, String> {
+    let date = event.start.date();
+    let Some(record) = config.time_records.iter().find(|r| r.date == date) else {
+        return Err(format!("no workday recorded on {}, please record it first", date));
+    };
+    let project = suggest_project(config, event);
+    let already_recorded = record.project_entries
+        .iter()
+        .any(|e| e.activity == event.summary && project.as_ref().is_none_or(|p| p == &e.project_name.code));
+    if already_recorded {
+        return Err(String::from("already recorded"));
+    }
+    // Entries are proposed in quarters, so less than a quarter left is nothing left
+    let remaining = record.remaining_hours();
+    if remaining < 0.25 {
+        return Err(String::from("no hours left to record on this day"));
+    }
+
+    let duration = TimeRecord::round_quarter((event.end - event.start).num_seconds() as f64 / 3600.0);
+    Ok(Suggestion {
+        date,
+        project,
+        hours: duration.max(0.25).min(remaining),
+        activity: event.summary.chars().take(MAX_ACTIVITY_LEN).collect(),
+    })
+}
+
+/// Lets the user change project, hours, activity and tags of a suggestion.
+/// Returns:
+///     None if the user cancelled
+fn edit_suggestion(config: &mut Config, suggestion: &Suggestion) -> Result<Option<ProjectEntry>> {
+    let record = config.time_records.iter().find(|r| r.date == suggestion.date).context("Workday not found")?.clone();
+
+    let Some(code) = choose_project(&config.project_records, "For what project?")? else {
+        return Ok(None);
+    };
+    let project = find_project(&config.project_records, &code).context("Project not found")?;
+    let Some(hours) = CustomType::<f64>::new("How many hours?")
+        .with_default(suggestion.hours)
+        .with_error_message("Please type in a valid number!")
+        .with_help_message(&format!("{} hours are left on this day", record.remaining_hours()))
+        .with_validator(move |input: &f64| match record.validate_hours(*input) {
+            Ok(()) => Ok(Validation::Valid),
+            Err(msg) => Ok(Validation::Invalid(msg.into())),
+        })
+        .prompt_skippable()? else {
+        return Ok(None);
+    };
+    let Some(activity) = Text::new("What did you do?:")
+        .with_default(&suggestion.activity)
+        .with_validator(|input: &str| match ProjectEntry::validate_activity(input) {
+            Ok(()) => Ok(Validation::Valid),
+            Err(msg) => Ok(Validation::Invalid(msg.into())),
+        })
+        .prompt_skippable()? else {
+        return Ok(None);
+    };
+    let Some(tags) = choose_tags(&mut config.settings, &activity, &[])? else {
+        return Ok(None);
+    };
+    Ok(Some(ProjectEntry { id: 0, project_name: project, hours, activity, tags, invoice: None, history: vec![] }))
+}
+
+/// Goes through the events of a calendar file and lets the user accept, edit or skip the suggested entries.
+/// Future events are left out. A dry run only lists the suggestions
+pub fn import_ics(config: &mut Config, path: &Path, range: DateRange, dry_run: bool) -> Result<()> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {:#?}", path))?;
+    let today = Local::now().date_naive();
+    let events: Vec<CalendarEvent> = parse_ics(&contents, range)?
+        .into_iter()
+        .filter(|e| e.start.date() <= today)
+        .collect();
+    if events.is_empty() {
+        println!("No events found ({})", range);
+        return Ok(());
+    }
+
+    let mut added = 0;
+    for event in &events {
+        let organizer = if event.organizer.is_empty() { String::new() } else { format!(" ({})", event.organizer) };
+        println!("{} {}-{} {}{}", event.start.date(), event.start.format("%H:%M"), event.end.format("%H:%M"), event.summary, organizer);
+        let suggestion = match suggest_entry(config, event) {
+            Ok(suggestion) => suggestion,
+            Err(reason) => {
+                println!("  skipped: {}", reason);
+                continue;
+            }
+        };
+        println!("  -> {} {} h", suggestion.project.as_deref().unwrap_or("(no project)"), suggestion.hours);
+        if dry_run {
+            continue;
+        }
+
+        let mut options = vec!["Edit", "Skip", "Stop"];
+        if suggestion.project.is_some() {
+            options.insert(0, "Accept");
+        }
+        let entry = match Select::new("What would you like to do?", options).prompt_skippable()? {
+            Some("Accept") => {
+                let code = suggestion.project.as_deref().unwrap_or_default();
+                let project = find_project(&config.project_records, code).context("Project not found")?;
+                let tags = ProjectEntry::extract_tags(&suggestion.activity);
+                ProjectEntry { id: 0, project_name: project, hours: suggestion.hours, activity: suggestion.activity.clone(), tags, invoice: None, history: vec![] }
+            },
+            Some("Edit") => match edit_suggestion(config, &suggestion)? {
+                Some(entry) => entry,
+                None => {
+                    println!("  skipped");
+                    continue;
+                }
+            },
+            Some("Skip") => continue,
+            _ => break,
+        };
+        match config.add_project_entry(suggestion.date, entry) {
+            Ok(()) => added += 1,
+            Err(e) => println!("  {}", e),
+        }
+    }
+    if !dry_run {
+        println!("{} entries added from {} events", added, events.len());
+    }
+    Ok(())
+}
+
+/// Menu entry to import calendar events as entries
+pub fn import_ics_menu(config: &mut Config) -> Result<()> {
+
+    let Some(path) = Text::new("Calendar file (.ics):").prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(range) = choose_date_range(config)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    import_ics(config, Path::new(&path), range, false)
+}
+
+/// Lets the user list, add and delete the rules mapping calendar events to projects
+pub fn calendar_rules_menu(config: &mut Config) -> Result<()> {
+
+    loop {
+        let options = vec!["List Rules", "Add Rule", "Delete Rule", "Back"];
+        match Select::new("Calendar Rules", options).prompt_skippable()? {
+            Some("List Rules") => {
+                if config.settings.calendar_rules.is_empty() {
+                    println!("No rules yet. Events naming a project code in their summary are still matched");
+                }
+                for (i, rule) in config.settings.calendar_rules.iter().enumerate() {
+                    println!("{}. {}", i + 1, rule);
+                }
+            },
+            Some("Add Rule") => {
+                let Some(keyword) = Text::new("Keyword in summary or description:")
+                    .with_help_message("Leave empty to match by organizer only")
+                    .prompt_skippable()? else { continue };
+                let Some(organizer) = Text::new("Organizer name or address:")
+                    .with_help_message("Leave empty to match by keyword only")
+                    .prompt_skippable()? else { continue };
+                let keyword = Some(keyword.trim().to_string()).filter(|k| !k.is_empty());
+                let organizer = Some(organizer.trim().to_string()).filter(|o| !o.is_empty());
+                if keyword.is_none() && organizer.is_none() {
+                    println!("A rule needs a keyword or an organizer");
+                    continue;
+                }
+                let Some(project) = choose_project(&config.project_records, "Which project?")? else { continue };
+                config.settings.calendar_rules.push(CalendarRule { keyword, organizer, project });
+                config.save()?;
+            },
+            Some("Delete Rule") => {
+                let rules: Vec<String> = config.settings.calendar_rules.iter().map(|r| r.to_string()).collect();
+                if rules.is_empty() {
+                    println!("No rules to delete");
+                    continue;
+                }
+                if let Some(rule) = Select::new("Which rule?", rules.clone()).prompt_skippable()? {
+                    let index = rules.iter().position(|r| r == &rule).expect("rule was listed");
+                    config.settings.calendar_rules.remove(index);
+                    config.save()?;
+                }
+            },
+            _ => break Ok(()),
+        }
+    }
+}
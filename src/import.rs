@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveTime};
+use inquire::{Confirm, Text};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::utils::find_project;
+
+/// One project entry read from an import file, together with the data of its workday.
+/// Workday fields may be missing for days that already exist or were defined by an earlier row
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// Line in the file, for error messages
+    pub line: usize,
+    pub date: NaiveDate,
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+    pub pause: Option<f64>,
+    /// Project code. Rows without project only define the workday
+    pub project: Option<String>,
+    pub hours: Option<f64>,
+    pub activity: String,
+    pub tags: Vec<String>
+}
+
+/// Checked result of an import: the time records after it and what it adds
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub records: Vec<TimeRecord>,
+    pub new_days: Vec<NaiveDate>,
+    /// Ids of the added (or merged) entries
+    pub entries: Vec<u64>,
+    pub tags: Vec<String>
+}
+
+/// Validates rows against the rules of the prompts and applies them to a copy of the records.
+/// Every problem of the file is reported, nothing is changed if there is any
+pub fn plan_import(config: &Config, rows: Vec<ImportRow>) -> Result<ImportPlan> {
+    let policy = config.settings.duplicate_policy;
+    let mut records = config.time_records.clone();
+    let mut next_record_id = config.next_record_id();
+    let mut next_entry_id = config.next_entry_id();
+    let mut plan = ImportPlan { records: vec![], new_days: vec![], entries: vec![], tags: vec![] };
+    let mut errors: Vec<String> = Vec::new();
+
+    for row in rows {
+        let mut fail = |msg: String| errors.push(format!("line {}: {}", row.line, msg));
+
+        if let Err(e) = config.check_unlocked(row.date) {
+            fail(e.to_string());
+            continue;
+        }
+        let index = match records.iter().position(|r| r.date == row.date) {
+            Some(index) => {
+                let record = &records[index];
+                let differs = row.start.is_some_and(|t| t != record.start_time)
+                    || row.end.is_some_and(|t| t != record.end_time)
+                    || row.pause.is_some_and(|p| p != record.pause_minutes);
+                if differs {
+                    fail(format!("start, end or pause differ from the workday {} recorded before", row.date));
+                    continue;
+                }
+                index
+            },
+            None => {
+                let (Some(start_time), Some(end_time)) = (row.start, row.end) else {
+                    fail(format!("start and end are needed for the new workday {}", row.date));
+                    continue;
+                };
+                if end_time <= start_time {
+                    fail(String::from("end lies before start"));
+                    continue;
+                }
+                let pause_minutes = row.pause.unwrap_or(0.0);
+                if let Err(msg) = TimeRecord::validate_pause(pause_minutes) {
+                    fail(msg);
+                    continue;
+                }
+                records.push(TimeRecord { id: next_record_id, date: row.date, start_time, end_time, pause_minutes, project_entries: vec![], history: vec![] });
+                next_record_id += 1;
+                plan.new_days.push(row.date);
+                records.len() - 1
+            }
+        };
+
+        let Some(code) = row.project else {
+            continue;
+        };
+        let Some(project) = find_project(&config.project_records, &code) else {
+            fail(format!("unknown project code {}", code));
+            continue;
+        };
+        let Some(hours) = row.hours else {
+            fail(String::from("hours are missing"));
+            continue;
+        };
+        let record = &mut records[index];
+        if let Err(msg) = record.validate_hours(hours).and(ProjectEntry::validate_activity(&row.activity)) {
+            fail(msg);
+            continue;
+        }
+
+        let mut tags = row.tags;
+        for tag in ProjectEntry::extract_tags(&row.activity) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        plan.tags.extend(tags.iter().cloned());
+
+        let entry = ProjectEntry { id: next_entry_id, project_name: project, hours, activity: row.activity, tags, invoice: None, history: vec![] };
+        match record.insert_entry(entry, policy) {
+            Ok(Some(merged_id)) => plan.entries.push(merged_id),
+            Ok(None) => {
+                plan.entries.push(next_entry_id);
+                next_entry_id += 1;
+            },
+            Err(e) => fail(e.to_string()),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    records.sort_by_key(|r| r.date);
+    plan.records = records;
+    plan.tags.sort();
+    plan.tags.dedup();
+    Ok(plan)
+}
+
+impl ImportPlan {
+    /// Prints what the import would add
+    pub fn print_preview(&self) {
+        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+        for record in &self.records {
+            let new_day = self.new_days.contains(&record.date);
+            let entries: Vec<&ProjectEntry> = record.project_entries.iter().filter(|e| self.entries.contains(&e.id)).collect();
+            if !new_day && entries.is_empty() {
+                continue;
+            }
+            let marker = if new_day { "new day" } else { "existing day" };
+            println!("{} {}-{} pause {} ({})", record.date, record.start_time.format("%H:%M"), record.end_time.format("%H:%M"), record.pause_minutes, marker);
+            for entry in entries {
+                println!("  {}", entry.label());
+            }
+        }
+        println!("{} new days, {} entries", self.new_days.len(), self.entries.len());
+        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    }
+
+    /// Stores the imported records in a single save
+    pub fn apply(self, config: &mut Config) -> Result<()> {
+        config.time_records = self.records;
+        config.settings.add_tags(&self.tags);
+        config.save()?;
+        println!("Imported {} entries, {} new days", self.entries.len(), self.new_days.len());
+        Ok(())
+    }
+}
+
+/// Names of the CSV columns for each field. The defaults match the entries export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub date: String,
+    pub start: String,
+    pub end: String,
+    pub pause: String,
+    pub project: String,
+    pub hours: String,
+    pub activity: String,
+    pub tags: String
+}
+
+impl Default for ColumnMapping {
+    fn default() -> ColumnMapping {
+        ColumnMapping {
+            date: String::from("date"),
+            start: String::from("start"),
+            end: String::from("end"),
+            pause: String::from("pause"),
+            project: String::from("project"),
+            hours: String::from("hours"),
+            activity: String::from("activity"),
+            tags: String::from("tags"),
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// Maps a field to another column, given as `field=column`, e.g. `date=Datum`
+    pub fn set(&mut self, spec: &str) -> Result<()> {
+        let (field, column) = spec
+            .split_once('=')
+            .with_context(|| format!("'{}' is not a mapping like date=Datum", spec))?;
+        let target = match field.trim().to_lowercase().as_str() {
+            "date" => &mut self.date,
+            "start" => &mut self.start,
+            "end" => &mut self.end,
+            "pause" => &mut self.pause,
+            "project" => &mut self.project,
+            "hours" => &mut self.hours,
+            "activity" => &mut self.activity,
+            "tags" => &mut self.tags,
+            other => bail!("Unknown field '{}'. Use date, start, end, pause, project, hours, activity or tags", other),
+        };
+        *target = column.trim().to_string();
+        Ok(())
+    }
+}
+
+/// Parses a date like 2025-11-09, 09.11.2025 or 11/09/2025 (US order, like Clockify)
+pub fn parse_import_date(value: &str) -> Result<NaiveDate, String> {
+    ["%Y-%m-%d", "%d.%m.%Y", "%m/%d/%Y"]
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+        .ok_or_else(|| format!("'{}' is not a date like 2025-11-09", value))
+}
+
+/// Parses a time like 08:00, 08:00:00 or 08:00:00 AM
+pub fn parse_import_time(value: &str) -> Result<NaiveTime, String> {
+    ["%H:%M", "%H:%M:%S", "%I:%M %p", "%I:%M:%S %p"]
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(value, f).ok())
+        .ok_or_else(|| format!("'{}' is not a time like 08:00", value))
+}
+
+/// Reads the rows of a CSV file. Empty cells count as missing
+pub fn read_csv_rows(contents: &str, mapping: &ColumnMapping, format: &CsvFormat) -> Result<Vec<ImportRow>> {
+    if !format.delimiter.is_ascii() {
+        bail!("The CSV delimiter must be an ASCII character, got '{}'", format.delimiter);
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(format.delimiter as u8)
+        .from_reader(contents.as_bytes());
+
+    let headers: HashMap<String, usize> = reader
+        .headers()
+        .context("Failed to read the CSV header")?
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.trim().to_string(), i))
+        .collect();
+    let column = |name: &str| headers.get(name).copied();
+    let Some(date_column) = column(&mapping.date) else {
+        bail!("The file has no column '{}' for the date", mapping.date);
+    };
+
+    let number = |value: &str| -> Result<f64, String> {
+        value
+            .replace(format.decimal_separator, ".")
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", value))
+    };
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        // Line 1 is the header
+        let line = i + 2;
+        let record = record.with_context(|| format!("Failed to read line {}", line))?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).map(str::trim).filter(|v| !v.is_empty());
+
+        let parsed = (|| -> Result<ImportRow, String> {
+            Ok(ImportRow {
+                line,
+                date: parse_import_date(field(Some(date_column)).unwrap_or_default())?,
+                start: field(column(&mapping.start)).map(parse_import_time).transpose()?,
+                end: field(column(&mapping.end)).map(parse_import_time).transpose()?,
+                pause: field(column(&mapping.pause)).map(number).transpose()?,
+                project: field(column(&mapping.project)).map(String::from),
+                hours: field(column(&mapping.hours)).map(number).transpose()?,
+                activity: field(column(&mapping.activity)).unwrap_or_default().to_string(),
+                tags: field(column(&mapping.tags))
+                    .map(|t| t.split([' ', ',']).filter(|t| !t.is_empty()).map(|t| t.trim_start_matches('#').to_lowercase()).collect())
+                    .unwrap_or_default(),
+            })
+        })();
+        match parsed {
+            Ok(row) => rows.push(row),
+            Err(msg) => errors.push(format!("line {}: {}", line, msg)),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    Ok(rows)
+}
+
+/// Checks a CSV file and imports it, or only previews it on a dry run
+pub fn import_csv(config: &mut Config, path: &Path, mapping: &ColumnMapping, format: &CsvFormat, dry_run: bool) -> Result<()> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {:#?}", path))?;
+    let plan = plan_import(config, read_csv_rows(&contents, mapping, format)?)?;
+    plan.print_preview();
+    if dry_run {
+        println!("Dry run, nothing was imported");
+        return Ok(());
+    }
+    plan.apply(config)
+}
+
+/// Menu entry to import a CSV file with the columns of the entries export
+pub fn import_csv_menu(config: &mut Config) -> Result<()> {
+
+    let Some(path) = Text::new("File to import:")
+        .with_help_message("Columns like the entries export: date, start, end, pause, project, hours, activity, tags")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let plan = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {:#?}", path))
+        .and_then(|contents| read_csv_rows(&contents, &ColumnMapping::default(), &config.settings.csv))
+        .and_then(|rows| plan_import(config, rows));
+    let plan = match plan {
+        Ok(plan) => plan,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    plan.print_preview();
+
+    let confirm = Confirm::new("Import these entries?").prompt_skippable()?;
+    if confirm == Some(true) {
+        plan.apply(config)?;
+    } else {
+        println!("Nothing imported");
+    }
+    Ok(())
+}
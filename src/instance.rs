@@ -0,0 +1,172 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use chrono::{Local, NaiveDateTime, SubsecRound};
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::journal::{Journal, Snapshot, merge};
+use crate::locks::Locks;
+use crate::seals::Seals;
+use crate::history::Tombstones;
+
+/// File name of the lock a running ptt holds on the data directory
+pub const INSTANCE_LOCK_FILE: &str = ".ptt.lock";
+
+/// Path of the lock taken by this process, to release it on exit
+static HELD_LOCK: OnceLock<PathBuf> = OnceLock::new();
+
+/// The ptt holding the data directory, as written into the lock file
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub started: NaiveDateTime
+}
+
+/// Advisory lock on the data directory, released when dropped. The lock file is locked with
+/// the file lock of the OS as well, which ends with the process, even if it crashes
+#[derive(Debug)]
+pub struct InstanceLock {
+    pub path: PathBuf,
+    file: File
+}
+
+/// Whether the path still leads to the opened file. Its previous holder removes it on exit
+fn is_current(path: &Path, file: &File) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(path), file.metadata()) {
+            (Ok(on_disk), Ok(opened)) => on_disk.dev() == opened.dev() && on_disk.ino() == opened.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = file;
+        path.exists()
+    }
+}
+
+impl InstanceLock {
+    /// Takes the lock for this process. A lock left behind by a ptt that isn't running
+    /// anymore is taken over, a lock of a running one is an error naming it
+    pub fn acquire(path: &Path) -> Result<InstanceLock> {
+        let holder = LockHolder { pid: std::process::id(), started: Local::now().naive_local().trunc_subsecs(0) };
+        let json = serde_json::to_string(&holder).context("Failed to serialize the lock holder")?;
+        for _ in 0..3 {
+            let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)
+                .with_context(|| format!("Failed to open lock {:#?}", path))?;
+            match file.try_lock() {
+                Ok(()) => {},
+                Err(TryLockError::WouldBlock) => {
+                    let contents = fs::read_to_string(path).unwrap_or_default();
+                    let other = match serde_json::from_str::<LockHolder>(&contents) {
+                        Ok(other) => format!(" (pid {}, started {})", other.pid, other.started.format("%Y-%m-%d %H:%M")),
+                        Err(_) => String::new(),
+                    };
+                    bail!("Another ptt{} is using the data in {}. Close it first", other, path.parent().unwrap_or(path).display());
+                },
+                Err(TryLockError::Error(e)) => return Err(e).with_context(|| format!("Failed to lock {:#?}", path)),
+            }
+            if !is_current(path, &file) {
+                continue;
+            }
+
+            let mut previous = String::new();
+            let _ = file.read_to_string(&mut previous);
+            if serde_json::from_str::<LockHolder>(&previous).is_ok_and(|h| h.pid != holder.pid) {
+                println!("Taking over the lock {:#?} left behind by a ptt that isn't running anymore", path);
+            }
+            file.set_len(0).and_then(|_| file.seek(SeekFrom::Start(0))).and_then(|_| file.write_all(json.as_bytes()))
+                .with_context(|| format!("Failed to write lock {:#?}", path))?;
+            let _ = HELD_LOCK.set(path.to_path_buf());
+            return Ok(InstanceLock { path: path.to_path_buf(), file });
+        }
+        bail!("Failed to take the lock {:#?}", path)
+    }
+}
+
+/// Releases the lock of this process and exits. std::process::exit skips the drop of the lock
+pub fn release_and_exit(code: i32) -> ! {
+    if let Some(path) = HELD_LOCK.get()
+        && let Ok(contents) = fs::read_to_string(path)
+        && serde_json::from_str::<LockHolder>(&contents).is_ok_and(|h| h.pid == std::process::id())
+    {
+        let _ = fs::remove_file(path);
+    }
+    std::process::exit(code)
+}
+
+impl Drop for InstanceLock {
+    /// Removes the file while still holding it, so whoever opened it meanwhile sees it is gone
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Modification time and length of the data files and the journal, to notice writes by others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskStamp(Vec<Option<(SystemTime, u64)>>);
+
+impl DiskStamp {
+    pub fn of(paths: &[&Path]) -> DiskStamp {
+        DiskStamp(paths.iter().map(|p| {
+            let meta = fs::metadata(p).ok()?;
+            Some((meta.modified().ok()?, meta.len()))
+        }).collect())
+    }
+}
+
+/// Reads a JSON file, a missing or empty one gives the default
+fn read_or_default<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match fs::read_to_string(path) {
+        Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to deserialze data for path: {:#?}", path)),
+        _ => Ok(T::default()),
+    }
+}
+
+impl Config {
+    /// Stamp of the files a save depends on
+    pub fn disk_stamp(&self) -> DiskStamp {
+        DiskStamp::of(&[&self.t_path, &self.p_path, &self.s_path, &self.journal.path])
+    }
+
+    /// Reloads the data if the files changed on disk since they were loaded or last saved
+    /// and merges the unsaved changes into it. Does nothing for a Config not loaded from disk
+    pub fn merge_from_disk(&mut self) -> Result<()> {
+        let Some(loaded) = &self.on_disk else { return Ok(()) };
+        let stamp = self.disk_stamp();
+        if *loaded == stamp {
+            return Ok(());
+        }
+        let files = Snapshot {
+            time_records: read_or_default(&self.t_path)?,
+            project_records: read_or_default(&self.p_path)?,
+            settings: read_or_default(&self.s_path)?,
+        };
+        let journal = Journal::open(&self.t_path, files)?;
+        let (merged, notes) = merge(&self.journal.base, &Snapshot::of(self), &journal.base);
+        if merged != journal.base {
+            println!("The data files were changed by someone else in the meantime, your changes are merged into them");
+        }
+        for note in notes {
+            println!("  {}", note);
+        }
+        let Snapshot { time_records, project_records, settings } = merged;
+        (self.time_records, self.project_records, self.settings) = (time_records, project_records, settings);
+        self.journal = journal;
+        self.locks = Locks::open(&self.t_path)?;
+        self.seals = Seals::open(&self.t_path)?;
+        self.tombstones.merge(Tombstones::open(&self.t_path)?);
+        self.on_disk = Some(stamp);
+        Ok(())
+    }
+}
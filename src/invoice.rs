@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::{Local, NaiveDate};
+use inquire::{Confirm, CustomType, Select, Text};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::query::Query;
+use crate::pdf::{Font, PdfDocument};
+use crate::render::{html_escape, html_page};
+use crate::reports::choose_date_range;
+use crate::export::{write_bytes, write_output};
+
+/// Output format of an invoice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum InvoiceFormat {
+    Pdf,
+    Html
+}
+
+impl InvoiceFormat {
+    /// Usual file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            InvoiceFormat::Pdf => "pdf",
+            InvoiceFormat::Html => "html",
+        }
+    }
+}
+
+impl std::fmt::Display for InvoiceFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceFormat::Pdf => write!(f, "PDF"),
+            InvoiceFormat::Html => write!(f, "HTML"),
+        }
+    }
+}
+
+/// Rounds an amount to cents
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Line item of an invoice: the unbilled hours of one project
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceLine {
+    pub project: String,
+    pub hours: f64,
+    pub rate: f64,
+    /// Ids of the billed project entries
+    pub entries: Vec<u64>
+}
+
+impl InvoiceLine {
+    pub fn amount(&self) -> f64 {
+        cents(self.hours * self.rate)
+    }
+}
+
+/// Invoice over the unbilled hours of a client
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invoice {
+    pub number: String,
+    pub date: NaiveDate,
+    pub client: String,
+    /// First and last day with billed hours
+    pub period: (NaiveDate, NaiveDate),
+    pub issuer: Vec<String>,
+    pub currency: String,
+    pub vat_percent: f64,
+    pub lines: Vec<InvoiceLine>
+}
+
+impl Invoice {
+    /// Collects the unbilled entries of the client's projects in a date range.
+    /// The invoice gets the next number of the settings, which is only used up by `bill`
+    pub fn collect(config: &Config, client: &str, range: DateRange, date: NaiveDate) -> Result<Invoice> {
+        let projects: Vec<&Project> = config.projects().filter(|p| p.client.as_deref() == Some(client)).collect();
+        if projects.is_empty() {
+            bail!("No projects for client {}", client);
+        }
+
+        let mut query = Query::for_range(range);
+        query.projects = projects.iter().map(|p| p.code.clone()).collect();
+        let hits: Vec<_> = query.entries(config).into_iter().filter(|hit| hit.entry.invoice.is_none()).collect();
+        if hits.is_empty() {
+            bail!("No unbilled hours for client {} ({})", client, range);
+        }
+
+        let mut lines: BTreeMap<&str, InvoiceLine> = BTreeMap::new();
+        for hit in &hits {
+            let code = hit.entry.project_name.code.as_str();
+            let project = projects.iter().find(|p| p.code == code).context("Project of the entry not found")?;
+            let Some(rate) = project.rate else {
+                bail!("Project {} has no hourly rate", code);
+            };
+            let line = lines.entry(code).or_insert_with(|| InvoiceLine { project: code.to_string(), hours: 0.0, rate, entries: vec![] });
+            line.hours += hit.entry.hours;
+            line.entries.push(hit.entry.id);
+        }
+
+        let settings = &config.settings.invoice;
+        Ok(Invoice {
+            number: settings.next_invoice_number(),
+            date,
+            client: client.to_string(),
+            period: (hits[0].record.date, hits[hits.len() - 1].record.date),
+            issuer: settings.issuer.clone(),
+            currency: settings.currency.clone(),
+            vat_percent: settings.vat_percent,
+            lines: lines.into_values().collect(),
+        })
+    }
+
+    pub fn hours(&self) -> f64 {
+        self.lines.iter().map(|l| l.hours).sum()
+    }
+
+    pub fn net(&self) -> f64 {
+        self.lines.iter().map(|l| l.amount()).sum()
+    }
+
+    pub fn vat(&self) -> f64 {
+        cents(self.net() * self.vat_percent / 100.0)
+    }
+
+    pub fn total(&self) -> f64 {
+        self.net() + self.vat()
+    }
+
+    /// Ids of all billed entries
+    pub fn entry_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.lines.iter().flat_map(|l| l.entries.iter().copied())
+    }
+
+    fn money(&self, amount: f64) -> String {
+        format!("{:.2} {}", amount, self.currency)
+    }
+
+    fn period_label(&self) -> String {
+        format!("{} - {}", self.period.0.format("%d.%m.%Y"), self.period.1.format("%d.%m.%Y"))
+    }
+}
+
+/// Clients of the projects, sorted
+pub fn clients(config: &Config) -> Vec<String> {
+    let mut clients: Vec<String> = config.projects().filter_map(|p| p.client.clone()).collect();
+    clients.sort();
+    clients.dedup();
+    clients
+}
+
+/// Marks the entries of an invoice as billed and uses up its number.
+/// Nothing is marked if any of the entries is gone or billed already
+pub fn bill(config: &mut Config, invoice: &Invoice) -> Result<()> {
+    for id in invoice.entry_ids() {
+        let (_, entry) = config.find_entry(id).with_context(|| format!("No project entry with id #{}", id))?;
+        if let Some(number) = &entry.invoice {
+            bail!("Project entry #{} is already billed with invoice {}", id, number);
+        }
+    }
+    for id in invoice.entry_ids() {
+        config.find_entry_mut(id).expect("entry was checked").invoice = Some(invoice.number.clone());
+    }
+    config.settings.invoice.next_number += 1;
+    config.save()
+}
+
+/// Renders an invoice as standalone HTML page
+pub fn invoice_html(invoice: &Invoice) -> String {
+    let title = format!("Invoice {}", invoice.number);
+    let mut body = String::new();
+
+    if !invoice.issuer.is_empty() {
+        body.push_str(&format!("<p>{}</p>\n", invoice.issuer.iter().map(|l| html_escape(l)).collect::<Vec<_>>().join("<br>\n")));
+    }
+    body.push_str(&format!("<p><strong>To:</strong> {}</p>\n", html_escape(&invoice.client)));
+    body.push_str(&format!("<h1>{}</h1>\n", html_escape(&title)));
+    body.push_str(&format!(
+        "<p>Date: {}<br>\nService period: {}</p>\n",
+        invoice.date.format("%d.%m.%Y"),
+        invoice.period_label()
+    ));
+
+    body.push_str("<table>\n<tr><th>Project</th><th class=\"num\">Hours</th><th class=\"num\">Rate</th><th class=\"num\">Amount</th></tr>\n");
+    for line in &invoice.lines {
+        body.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+            html_escape(&line.project),
+            line.hours,
+            html_escape(&invoice.money(line.rate)),
+            html_escape(&invoice.money(line.amount()))
+        ));
+    }
+    for (label, amount) in [
+        (String::from("Net"), invoice.net()),
+        (format!("VAT {}%", invoice.vat_percent), invoice.vat()),
+        (String::from("Total"), invoice.total()),
+    ] {
+        body.push_str(&format!(
+            "<tr class=\"total\"><td>{}</td><td></td><td></td><td class=\"num\">{}</td></tr>\n",
+            html_escape(&label),
+            html_escape(&invoice.money(amount))
+        ));
+    }
+    body.push_str("</table>\n");
+
+    html_page(&title, &body)
+}
+
+/// Lays out an invoice as PDF
+pub fn invoice_pdf(invoice: &Invoice) -> Vec<u8> {
+    const LEFT: f64 = PdfDocument::MARGIN;
+    const RIGHT: f64 = PdfDocument::WIDTH - PdfDocument::MARGIN;
+    const SIZE: f64 = 10.0;
+    const LINE: f64 = 14.0;
+    let mut doc = PdfDocument::new();
+
+    for line in &invoice.issuer {
+        let y = doc.next_line(LINE);
+        doc.text(LEFT, y, SIZE, Font::Regular, line);
+    }
+    doc.next_line(20.0);
+    let y = doc.next_line(LINE);
+    doc.text(LEFT, y, SIZE, Font::Bold, &invoice.client);
+    doc.next_line(30.0);
+
+    let y = doc.next_line(20.0);
+    doc.text(LEFT, y, 16.0, Font::Bold, &format!("Invoice {}", invoice.number));
+    for (label, value) in [("Date", invoice.date.format("%d.%m.%Y").to_string()), ("Service period", invoice.period_label())] {
+        let y = doc.next_line(LINE);
+        doc.text(LEFT, y, SIZE, Font::Bold, label);
+        doc.text(LEFT + 90.0, y, SIZE, Font::Regular, &value);
+    }
+    doc.next_line(10.0);
+
+    let y = doc.next_line(LINE + 4.0);
+    doc.text(LEFT, y, SIZE, Font::Bold, "Project");
+    doc.text_right(330.0, y, SIZE, Font::Bold, "Hours");
+    doc.text_right(420.0, y, SIZE, Font::Bold, "Rate");
+    doc.text_right(RIGHT, y, SIZE, Font::Bold, "Amount");
+    doc.line(LEFT, y - 3.0, RIGHT, y - 3.0, 0.8);
+    for line in &invoice.lines {
+        let y = doc.next_line(LINE);
+        doc.text(LEFT, y, SIZE, Font::Regular, &line.project);
+        doc.text_right(330.0, y, SIZE, Font::Regular, &format!("{:.2}", line.hours));
+        doc.text_right(420.0, y, SIZE, Font::Regular, &invoice.money(line.rate));
+        doc.text_right(RIGHT, y, SIZE, Font::Regular, &invoice.money(line.amount()));
+    }
+    let y = doc.next_line(6.0);
+    doc.line(LEFT, y, RIGHT, y, 0.5);
+
+    for (label, amount, font) in [
+        (String::from("Net"), invoice.net(), Font::Regular),
+        (format!("VAT {}%", invoice.vat_percent), invoice.vat(), Font::Regular),
+        (String::from("Total"), invoice.total(), Font::Bold),
+    ] {
+        let y = doc.next_line(LINE);
+        doc.text(LEFT, y, SIZE, font, &label);
+        doc.text_right(RIGHT, y, SIZE, font, &invoice.money(amount));
+    }
+
+    doc.to_bytes()
+}
+
+/// Prints the line items of an invoice
+pub fn print_invoice(invoice: &Invoice) {
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    println!("Invoice {} for {} ({})", invoice.number, invoice.client, invoice.period_label());
+    for line in &invoice.lines {
+        println!("{:<20} {:>8.2} h x {:>10} = {:>12}", line.project, line.hours, invoice.money(line.rate), invoice.money(line.amount()));
+    }
+    println!("Net: {}, VAT {}%: {}, Total: {}", invoice.money(invoice.net()), invoice.vat_percent, invoice.money(invoice.vat()), invoice.money(invoice.total()));
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+}
+
+/// Writes the invoice file
+pub fn write_invoice(invoice: &Invoice, format: InvoiceFormat, path: &Path) -> Result<()> {
+    match format {
+        InvoiceFormat::Pdf => write_bytes(&invoice_pdf(invoice), path),
+        InvoiceFormat::Html => write_output(&invoice_html(invoice), Some(path)),
+    }
+}
+
+/// Writes the invoice file and marks its entries as billed. The file is removed again if
+/// billing fails, so no invoice is sent out with a number that will be issued again
+pub fn issue_invoice(config: &mut Config, invoice: &Invoice, format: InvoiceFormat, path: &Path) -> Result<()> {
+    write_invoice(invoice, format, path)?;
+    if let Err(e) = bill(config, invoice) {
+        let _ = fs::remove_file(path);
+        println!("Removed {:#?} again, nothing was billed", path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Menu entry to create an invoice for a client and mark its entries as billed
+pub fn create_invoice_menu(config: &mut Config) -> Result<()> {
+
+    let clients = clients(config);
+    if clients.is_empty() {
+        println!("No project has a client yet. Set one under 'Rates and Clients' first.");
+        return Ok(());
+    }
+    let Some(client) = Select::new("Which client?", clients).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(range) = choose_date_range(config)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let invoice = match Invoice::collect(config, &client, range, Local::now().date_naive()) {
+        Ok(invoice) => invoice,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    print_invoice(&invoice);
+
+    let Some(format) = Select::new("Which format?", vec![InvoiceFormat::Pdf, InvoiceFormat::Html]).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let default_name = format!("{}.{}", invoice.number, format.extension());
+    let Some(path) = Text::new("File name:")
+        .with_default(&default_name)
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let confirm = Confirm::new(&format!("Create invoice {} and mark {} entries as billed?", invoice.number, invoice.entry_ids().count()))
+        .prompt_skippable()?;
+    if confirm != Some(true) {
+        println!("Invoice NOT created");
+        return Ok(());
+    }
+
+    issue_invoice(config, &invoice, format, Path::new(&path))
+}
+
+/// Lets the user set hourly rate and client of a project
+pub fn edit_rate_and_client(config: &mut Config) -> Result<()> {
+
+    let codes: Vec<String> = config.projects().map(|p| p.code.clone()).collect();
+    if codes.is_empty() {
+        println!("Currently no stored projects.");
+        return Ok(());
+    }
+    let Some(code) = Select::new("Which project?", codes).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let project = config.project_records.iter().find(|p| p.code == code).context("Project not found")?.clone();
+
+    let Some(rate) = CustomType::<f64>::new("Hourly rate:")
+        .with_default(project.rate.unwrap_or(0.0))
+        .with_error_message("Please type in a valid number")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(client) = Text::new("Client:")
+        .with_default(project.client.as_deref().unwrap_or_default())
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    if let Some(project) = config.project_records.iter_mut().find(|p| p.code == code) {
+        project.rate = Some(rate);
+        project.client = Some(client.trim().to_string()).filter(|c| !c.is_empty());
+    }
+    config.save()?;
+    Ok(())
+}
+
+/// Lets the user edit issuer, VAT, currency and numbering of invoices
+pub fn edit_invoice_settings(config: &mut Config) -> Result<()> {
+
+    let current = config.settings.invoice.clone();
+    let Some(issuer) = Text::new("Issuer:")
+        .with_default(&current.issuer.join("; "))
+        .with_help_message("Name and address, lines separated by ';'")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(vat_percent) = CustomType::<f64>::new("VAT in percent:")
+        .with_default(current.vat_percent)
+        .with_error_message("Please type in a valid number")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(currency) = Text::new("Currency:").with_default(&current.currency).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(prefix) = Text::new("Invoice number prefix:").with_default(&current.prefix).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(next_number) = CustomType::<u32>::new("Next invoice number:")
+        .with_default(current.next_number)
+        .with_error_message("Please type in a valid number")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    config.settings.invoice = InvoiceSettings {
+        issuer: issuer.split(';').map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect(),
+        vat_percent,
+        currency,
+        prefix,
+        next_number,
+    };
+    config.save()?;
+    Ok(())
+}
@@ -0,0 +1,427 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, SubsecRound};
+use serde::{Serialize, Deserialize};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+
+/// File name of the journal, next to the data files
+pub const JOURNAL_FILE: &str = "journal.jsonl";
+/// File name of the compacted journal lines
+pub const ARCHIVE_FILE: &str = "journal.archive.jsonl";
+/// Number of journal lines after which a save compacts the journal into the data files
+pub const COMPACT_AFTER: usize = 500;
+
+/// A single change of the data. Applying a change twice gives the same result,
+/// so a journal can be replayed onto data files that already contain some of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Change {
+    RecordAdded { record: TimeRecord },
+    RecordEdited {
+        id: u64,
+        date: NaiveDate,
+        start_time: NaiveTime,
+        end_time: NaiveTime,
+        pause_minutes: f64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        history: Vec<Revision>
+    },
+    RecordDeleted { id: u64 },
+    RecordsOrdered { ids: Vec<u64> },
+    EntryAdded { record: u64, entry: ProjectEntry },
+    EntryEdited { record: u64, entry: ProjectEntry },
+    EntryDeleted { record: u64, id: u64 },
+    EntriesOrdered { record: u64, ids: Vec<u64> },
+    ProjectAdded { project: Project },
+    ProjectEdited { project: Project },
+    ProjectDeleted { code: String },
+    ProjectsOrdered { codes: Vec<String> },
+    SettingsChanged { settings: Settings },
+    IdsUsed { record: u64, entry: u64 }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Change::RecordAdded { record } => write!(f, "workday {} added", record.date),
+            Change::RecordEdited { date, .. } => write!(f, "workday {} changed", date),
+            Change::RecordDeleted { id } => write!(f, "workday #{} deleted", id),
+            Change::RecordsOrdered { .. } => write!(f, "workdays reordered"),
+            Change::EntryAdded { entry, .. } => write!(f, "entry #{} added ({} {} h)", entry.id, entry.project_name.code, entry.hours),
+            Change::EntryEdited { entry, .. } => write!(f, "entry #{} changed ({} {} h)", entry.id, entry.project_name.code, entry.hours),
+            Change::EntryDeleted { id, .. } => write!(f, "entry #{} deleted", id),
+            Change::EntriesOrdered { record, .. } => write!(f, "entries of workday #{} reordered", record),
+            Change::ProjectAdded { project } => write!(f, "project {} added", project.code),
+            Change::ProjectEdited { project } => write!(f, "project {} changed", project.code),
+            Change::ProjectDeleted { code } => write!(f, "project {} deleted", code),
+            Change::ProjectsOrdered { .. } => write!(f, "projects reordered"),
+            Change::SettingsChanged { .. } => write!(f, "settings changed"),
+            Change::IdsUsed { record, entry } => write!(f, "ids up to workday #{} and entry #{} used", record, entry),
+        }
+    }
+}
+
+/// One line of the journal: a change and when it was saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEvent {
+    pub at: NaiveDateTime,
+    #[serde(flatten)]
+    pub change: Change
+}
+
+/// The persisted part of the Config
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub time_records: Vec<TimeRecord>,
+    pub project_records: Vec<Project>,
+    pub settings: Settings
+}
+
+impl Snapshot {
+    pub fn of(config: &Config) -> Snapshot {
+        Snapshot {
+            time_records: config.time_records.clone(),
+            project_records: config.project_records.clone(),
+            settings: config.settings.clone(),
+        }
+    }
+}
+
+/// Journal of a Config: where it is written and the state of the last save
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    pub path: PathBuf,
+    pub archive_path: PathBuf,
+    /// State after the last save, the next save writes the changes against it
+    pub base: Snapshot,
+    /// Lines in the journal since the last compaction
+    pub events: usize
+}
+
+/// Whether `new` is not `old` with removed items left out and added ones appended
+fn reordered<K: PartialEq>(old: &[K], new: &[K]) -> bool {
+    let mut expected: Vec<&K> = old.iter().filter(|k| new.contains(k)).collect();
+    expected.extend(new.iter().filter(|k| !old.contains(k)));
+    !expected.into_iter().eq(new.iter())
+}
+
+/// Changes of the entries of a record
+fn diff_entries(old: &TimeRecord, new: &TimeRecord, changes: &mut Vec<Change>) {
+    for entry in &old.project_entries {
+        if !new.project_entries.iter().any(|e| e.id == entry.id) {
+            changes.push(Change::EntryDeleted { record: new.id, id: entry.id });
+        }
+    }
+    for entry in &new.project_entries {
+        match old.project_entries.iter().find(|e| e.id == entry.id) {
+            None => changes.push(Change::EntryAdded { record: new.id, entry: entry.clone() }),
+            Some(before) if before != entry => changes.push(Change::EntryEdited { record: new.id, entry: entry.clone() }),
+            Some(_) => {},
+        }
+    }
+    let old_ids: Vec<u64> = old.project_entries.iter().map(|e| e.id).collect();
+    let new_ids: Vec<u64> = new.project_entries.iter().map(|e| e.id).collect();
+    if reordered(&old_ids, &new_ids) {
+        changes.push(Change::EntriesOrdered { record: new.id, ids: new_ids });
+    }
+}
+
+/// Changes that turn one state into the other
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    for record in &old.time_records {
+        if !new.time_records.iter().any(|r| r.id == record.id) {
+            changes.push(Change::RecordDeleted { id: record.id });
+        }
+    }
+    for record in &new.time_records {
+        let Some(before) = old.time_records.iter().find(|r| r.id == record.id) else {
+            changes.push(Change::RecordAdded { record: record.clone() });
+            continue;
+        };
+        let workday_changed = before.date != record.date
+            || before.start_time != record.start_time
+            || before.end_time != record.end_time
+            || before.pause_minutes != record.pause_minutes
+            || before.history != record.history;
+        if workday_changed {
+            changes.push(Change::RecordEdited {
+                id: record.id,
+                date: record.date,
+                start_time: record.start_time,
+                end_time: record.end_time,
+                pause_minutes: record.pause_minutes,
+                history: record.history.clone(),
+            });
+        }
+        diff_entries(before, record, &mut changes);
+    }
+    let old_ids: Vec<u64> = old.time_records.iter().map(|r| r.id).collect();
+    let new_ids: Vec<u64> = new.time_records.iter().map(|r| r.id).collect();
+    if reordered(&old_ids, &new_ids) {
+        changes.push(Change::RecordsOrdered { ids: new_ids });
+    }
+
+    for project in &old.project_records {
+        if !new.project_records.iter().any(|p| p.code == project.code) {
+            changes.push(Change::ProjectDeleted { code: project.code.clone() });
+        }
+    }
+    for project in &new.project_records {
+        match old.project_records.iter().find(|p| p.code == project.code) {
+            None => changes.push(Change::ProjectAdded { project: project.clone() }),
+            Some(before) if before != project => changes.push(Change::ProjectEdited { project: project.clone() }),
+            Some(_) => {},
+        }
+    }
+    let old_codes: Vec<String> = old.project_records.iter().map(|p| p.code.clone()).collect();
+    let new_codes: Vec<String> = new.project_records.iter().map(|p| p.code.clone()).collect();
+    if reordered(&old_codes, &new_codes) {
+        changes.push(Change::ProjectsOrdered { codes: new_codes });
+    }
+
+    let uncounted = |settings: &Settings| Settings { last_record_id: 0, last_entry_id: 0, ..settings.clone() };
+    if uncounted(&old.settings) != uncounted(&new.settings) {
+        changes.push(Change::SettingsChanged { settings: new.settings.clone() });
+    }
+    if (old.settings.last_record_id, old.settings.last_entry_id) != (new.settings.last_record_id, new.settings.last_entry_id) {
+        changes.push(Change::IdsUsed { record: new.settings.last_record_id, entry: new.settings.last_entry_id });
+    }
+    changes
+}
+
+/// Sorts items into the given order, unknown ones last
+fn order_by<T, K: PartialEq>(items: &mut [T], order: &[K], key: impl Fn(&T) -> K) {
+    items.sort_by_key(|item| order.iter().position(|k| *k == key(item)).unwrap_or(order.len()));
+}
+
+/// Replaces the item with the same key or appends it
+fn upsert<T, K: PartialEq>(items: &mut Vec<T>, item: T, key: impl Fn(&T) -> K) {
+    match items.iter().position(|i| key(i) == key(&item)) {
+        Some(index) => items[index] = item,
+        None => items.push(item),
+    }
+}
+
+fn find_record(state: &mut Snapshot, id: u64) -> Option<&mut TimeRecord> {
+    state.time_records.iter_mut().find(|r| r.id == id)
+}
+
+impl Change {
+    /// Applies the change to a state. Changes of unknown records are ignored
+    pub fn apply(&self, state: &mut Snapshot) {
+        match self.clone() {
+            Change::RecordAdded { record } => upsert(&mut state.time_records, record, |r| r.id),
+            Change::RecordEdited { id, date, start_time, end_time, pause_minutes, history } => {
+                if let Some(record) = find_record(state, id) {
+                    record.date = date;
+                    record.start_time = start_time;
+                    record.end_time = end_time;
+                    record.pause_minutes = pause_minutes;
+                    record.history = history;
+                }
+            },
+            Change::RecordDeleted { id } => state.time_records.retain(|r| r.id != id),
+            Change::RecordsOrdered { ids } => order_by(&mut state.time_records, &ids, |r| r.id),
+            Change::EntryAdded { record, entry } | Change::EntryEdited { record, entry } => {
+                if let Some(record) = find_record(state, record) {
+                    upsert(&mut record.project_entries, entry, |e| e.id);
+                }
+            },
+            Change::EntryDeleted { record, id } => {
+                if let Some(record) = find_record(state, record) {
+                    record.project_entries.retain(|e| e.id != id);
+                }
+            },
+            Change::EntriesOrdered { record, ids } => {
+                if let Some(record) = find_record(state, record) {
+                    order_by(&mut record.project_entries, &ids, |e| e.id);
+                }
+            },
+            Change::ProjectAdded { project } | Change::ProjectEdited { project } => upsert(&mut state.project_records, project, |p| p.code.clone()),
+            Change::ProjectDeleted { code } => state.project_records.retain(|p| p.code != code),
+            Change::ProjectsOrdered { codes } => order_by(&mut state.project_records, &codes, |p| p.code.clone()),
+            Change::SettingsChanged { settings } => {
+                let used = (state.settings.last_record_id, state.settings.last_entry_id);
+                state.settings = settings;
+                Change::IdsUsed { record: used.0, entry: used.1 }.apply(state);
+            },
+            // The counters only go up, so an undo or a merge never hands out an id again
+            Change::IdsUsed { record, entry } => {
+                state.settings.last_record_id = state.settings.last_record_id.max(record);
+                state.settings.last_entry_id = state.settings.last_entry_id.max(entry);
+            },
+        }
+    }
+
+    /// Whether the change is worth showing. Counting the used ids is bookkeeping of the other changes
+    pub fn is_shown(&self) -> bool {
+        !matches!(self, Change::IdsUsed { .. })
+    }
+
+    /// What a change is about, changes of the same target conflict when merging
+    fn target(&self) -> String {
+        match self {
+            Change::RecordAdded { record } => format!("record {}", record.id),
+            Change::RecordEdited { id, .. } | Change::RecordDeleted { id } => format!("record {}", id),
+            Change::RecordsOrdered { .. } => String::from("records"),
+            Change::EntryAdded { entry, .. } | Change::EntryEdited { entry, .. } => format!("entry {}", entry.id),
+            Change::EntryDeleted { id, .. } => format!("entry {}", id),
+            Change::EntriesOrdered { record, .. } => format!("entries of {}", record),
+            Change::ProjectAdded { project } | Change::ProjectEdited { project } => format!("project {}", project.code),
+            Change::ProjectDeleted { code } => format!("project {}", code),
+            Change::ProjectsOrdered { .. } => String::from("projects"),
+            Change::SettingsChanged { .. } => String::from("settings"),
+            Change::IdsUsed { .. } => String::from("ids"),
+        }
+    }
+}
+
+/// Reads the events of a journal file. A missing file has none. A broken last line
+/// (from a write that was cut off) is skipped, broken lines before it are an error
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read path: {:#?}", path))?;
+    let lines: Vec<&str> = contents.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    let mut events = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(event) => events.push(event),
+            Err(_) if i + 1 == lines.len() => println!("Skipping the incomplete last line of {:#?}", path),
+            Err(e) => bail!("Failed to read line {} of {:#?}: {}", i + 1, path, e),
+        }
+    }
+    Ok(events)
+}
+
+/// Cuts a line that a cut-off write left without line end, so the next line starts on its own
+fn cut_partial_line(file: &mut File) -> std::io::Result<()> {
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    if contents.last().is_some_and(|b| *b != b'\n') {
+        let end = contents.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+        file.set_len(end as u64)?;
+    }
+    file.seek(SeekFrom::End(0))?;
+    Ok(())
+}
+
+impl Journal {
+    /// Journal next to the given data file, with the state loaded from the data files
+    pub fn open(data_path: &Path, base: Snapshot) -> Result<Journal> {
+        let path = data_path.with_file_name(JOURNAL_FILE);
+        let archive_path = data_path.with_file_name(ARCHIVE_FILE);
+        let events = read_journal(&path)?;
+        let mut base = base;
+        for event in &events {
+            event.change.apply(&mut base);
+        }
+        Ok(Journal { path, archive_path, base, events: events.len() })
+    }
+
+    /// Appends the changes as one line each
+    pub fn append(&mut self, changes: Vec<Change>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let at = Local::now().naive_local().trunc_subsecs(0);
+        let mut lines = String::new();
+        for change in changes {
+            lines.push_str(&serde_json::to_string(&JournalEvent { at, change }).context("Failed to serialize journal event")?);
+            lines.push('\n');
+            self.events += 1;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open journal {:#?}", self.path))?;
+        cut_partial_line(&mut file).with_context(|| format!("Failed to repair journal {:#?}", self.path))?;
+        file.write_all(lines.as_bytes())
+            .with_context(|| format!("Failed to write journal {:#?}", self.path))
+    }
+
+    /// Moves the journal lines to the archive, after the data files got the state written.
+    /// Keeps the complete history while the journal starts empty again
+    pub fn archive(&mut self) -> Result<()> {
+        if self.path.exists() {
+            let mut contents = String::new();
+            for event in read_journal(&self.path)? {
+                contents.push_str(&serde_json::to_string(&event).context("Failed to serialize journal event")?);
+                contents.push('\n');
+            }
+            let mut archive = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.archive_path)
+                .with_context(|| format!("Failed to open journal archive {:#?}", self.archive_path))?;
+            archive.write_all(contents.as_bytes())
+                .with_context(|| format!("Failed to write journal archive {:#?}", self.archive_path))?;
+            fs::write(&self.path, "").with_context(|| format!("Failed to empty journal {:#?}", self.path))?;
+        }
+        self.events = 0;
+        Ok(())
+    }
+}
+
+/// Applies the changes from `base` to `ours` onto `theirs`, a state saved by someone else since `base`.
+/// New workdays and entries get new ids if theirs used the same, a new workday on a date theirs
+/// added as well gets merged into it. Where both changed the same thing, ours is applied last
+/// Returns:
+///     The merged state and a note for every conflict
+pub fn merge(base: &Snapshot, ours: &Snapshot, theirs: &Snapshot) -> (Snapshot, Vec<String>) {
+    let mut ours = ours.clone();
+    let entry_ids = |state: &Snapshot| -> Vec<u64> {
+        state.time_records.iter().flat_map(|r| r.project_entries.iter().map(|e| e.id)).collect()
+    };
+    let (base_entries, their_entries) = (entry_ids(base), entry_ids(theirs));
+    let used = [&theirs.settings, &ours.settings];
+    let mut next_record = theirs.time_records.iter().chain(&ours.time_records).map(|r| r.id)
+        .chain(used.iter().map(|s| s.last_record_id)).max().unwrap_or(0) + 1;
+    let mut next_entry = their_entries.iter().chain(&entry_ids(&ours)).copied()
+        .chain(used.iter().map(|s| s.last_entry_id)).max().unwrap_or(0) + 1;
+    for record in ours.time_records.iter_mut() {
+        let new = !base.time_records.iter().any(|r| r.id == record.id);
+        if new && theirs.time_records.iter().any(|r| r.id == record.id) {
+            record.id = next_record;
+            next_record += 1;
+        }
+        for entry in record.project_entries.iter_mut() {
+            if !base_entries.contains(&entry.id) && their_entries.contains(&entry.id) {
+                entry.id = next_entry;
+                next_entry += 1;
+            }
+        }
+    }
+
+    let their_changes = diff(base, theirs);
+    let mut merged = theirs.clone();
+    let mut notes = Vec::new();
+    for change in diff(base, &ours) {
+        if let Change::RecordAdded { record } = &change
+            && let Some(existing) = merged.time_records.iter().find(|r| r.date == record.date && !base.time_records.iter().any(|b| b.id == r.id))
+        {
+            notes.push(format!("workday {} was added by both, your entries were added to it", record.date));
+            let id = existing.id;
+            for entry in &record.project_entries {
+                Change::EntryAdded { record: id, entry: entry.clone() }.apply(&mut merged);
+            }
+            continue;
+        }
+        if change.is_shown() && !their_changes.contains(&change) && their_changes.iter().any(|c| c.target() == change.target()) {
+            notes.push(format!("{} (changed by both, yours was applied last)", change));
+        }
+        change.apply(&mut merged);
+    }
+    (merged, notes)
+}
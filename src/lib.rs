@@ -2,4 +2,26 @@ pub mod models;
 pub mod ui;
 pub mod utils;
 pub mod storage;
+pub mod journal;
+pub mod undo;
+pub mod history;
+pub mod locks;
+pub mod seals;
+pub mod instance;
+pub mod git;
+pub mod reports;
+pub mod query;
+pub mod search;
+pub mod export;
+pub mod render;
+pub mod pdf;
+pub mod timesheet;
+pub mod xlsx;
+pub mod invoice;
+pub mod import;
+pub mod trackers;
+pub mod timeclock;
+pub mod org;
+pub mod ics;
+pub mod cli;
 pub mod test;
\ No newline at end of file
@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, SubsecRound};
+use inquire::{Select, Text, validator::Validation};
+use serde::{Serialize, Deserialize};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::journal::Snapshot;
+use crate::seals::print_verification;
+
+/// File name of the locked periods, next to the data files
+pub const LOCKS_FILE: &str = "locks.json";
+
+/// A submitted month or week whose workdays can't be changed anymore
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PeriodLock {
+    /// Month like 2025-11 or ISO week like 2025-W45
+    pub period: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub locked_at: NaiveDateTime
+}
+
+impl std::fmt::Display for PeriodLock {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} (submitted {})", self.period, self.locked_at.format("%Y-%m-%d %H:%M"))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LockAction {
+    Locked,
+    Unlocked
+}
+
+/// A lock or unlock of a period, kept for audits
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LockEvent {
+    pub at: NaiveDateTime,
+    pub action: LockAction,
+    pub period: String,
+    #[serde(default)]
+    pub reason: Option<String>
+}
+
+impl std::fmt::Display for LockEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let action = match self.action {
+            LockAction::Locked => "locked",
+            LockAction::Unlocked => "unlocked",
+        };
+        write!(f, "{} {} {}", self.at.format("%Y-%m-%d %H:%M"), self.period, action)?;
+        if let Some(reason) = &self.reason {
+            write!(f, ": {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Locked periods and the log of all locks and unlocks
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Locks {
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub locks: Vec<PeriodLock>,
+    #[serde(default)]
+    pub log: Vec<LockEvent>
+}
+
+/// Parses a month like 2025-11 or an ISO week like 2025-W45
+/// Returns:
+///     The normalized name of the period and its dates
+pub fn parse_period(s: &str) -> Result<(String, DateRange), String> {
+    let invalid = || format!("'{}' is not a month like 2025-11 or a week like 2025-W45", s);
+    let (year, rest) = s.trim().split_once('-').ok_or_else(invalid)?;
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    if let Some(week) = rest.strip_prefix('W').or_else(|| rest.strip_prefix('w')) {
+        let week: u32 = week.parse().map_err(|_| invalid())?;
+        let from = NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon).ok_or_else(invalid)?;
+        let to = NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Sun).ok_or_else(invalid)?;
+        return Ok((format!("{}-W{:02}", year, week), DateRange::new(from, to)));
+    }
+    let month: u32 = rest.parse().map_err(|_| invalid())?;
+    let range = DateRange::month(year, month).ok_or_else(invalid)?;
+    Ok((format!("{}-{:02}", year, month), range))
+}
+
+impl Locks {
+    /// Loads the locks next to the given data file. A missing file has none
+    pub fn open(data_path: &Path) -> Result<Locks> {
+        let path = data_path.with_file_name(LOCKS_FILE);
+        let mut locks: Locks = match fs::read_to_string(&path) {
+            Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to deserialze locks for path: {:#?}", path))?,
+            _ => Locks::default(),
+        };
+        locks.path = path;
+        Ok(locks)
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize locks")?;
+        fs::write(&self.path, json).with_context(|| format!("Failed to write locks to {:#?}", self.path))
+    }
+
+    /// The lock covering a date, if any
+    pub fn locked(&self, date: NaiveDate) -> Option<&PeriodLock> {
+        self.locks.iter().find(|l| l.from <= date && date <= l.to)
+    }
+
+    /// Locks overlapping a range of dates
+    pub fn overlapping(&self, range: DateRange) -> Vec<&PeriodLock> {
+        self.locks.iter().filter(|l| l.from <= range.to && range.from <= l.to).collect()
+    }
+
+    /// Marks a period as submitted. Does not write, call Locks.write after usage
+    pub fn lock(&mut self, period: &str) -> Result<&PeriodLock> {
+        let (period, range) = parse_period(period).map_err(anyhow::Error::msg)?;
+        if self.locks.iter().any(|l| l.period == period) {
+            bail!("{} is locked already", period);
+        }
+        let at = Local::now().naive_local().trunc_subsecs(0);
+        self.log.push(LockEvent { at, action: LockAction::Locked, period: period.clone(), reason: None });
+        self.locks.push(PeriodLock { period, from: range.from, to: range.to, locked_at: at });
+        self.locks.sort_by_key(|l| l.from);
+        Ok(self.locks.iter().find(|l| l.locked_at == at && l.from == range.from).expect("lock was added"))
+    }
+
+    /// Opens a locked period again. The reason is kept in the log.
+    /// Does not write, call Locks.write after usage
+    pub fn unlock(&mut self, period: &str, reason: &str) -> Result<()> {
+        let (period, _) = parse_period(period).map_err(anyhow::Error::msg)?;
+        if reason.trim().is_empty() {
+            bail!("Please give a reason for unlocking {}", period);
+        }
+        if !self.locks.iter().any(|l| l.period == period) {
+            bail!("{} is not locked", period);
+        }
+        self.locks.retain(|l| l.period != period);
+        let at = Local::now().naive_local().trunc_subsecs(0);
+        self.log.push(LockEvent { at, action: LockAction::Unlocked, period, reason: Some(reason.trim().to_string()) });
+        Ok(())
+    }
+}
+
+/// A workday without what billing adds to it: the invoice numbers of its entries and the
+/// revisions recording them. Billing leaves the hours as they are, so it is allowed in locked periods
+pub fn without_billing(record: &TimeRecord) -> TimeRecord {
+    let mut record = record.clone();
+    for entry in &mut record.project_entries {
+        entry.invoice = None;
+        for revision in &mut entry.history {
+            revision.changes.retain(|c| c.field != "invoice");
+        }
+        entry.history.retain(|r| !r.changes.is_empty());
+    }
+    record
+}
+
+/// Dates whose workdays differ between two states, apart from billing
+pub fn changed_dates(old: &Snapshot, new: &Snapshot) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    for record in &new.time_records {
+        match old.time_records.iter().find(|r| r.id == record.id) {
+            None => dates.push(record.date),
+            Some(before) if without_billing(before) != without_billing(record) => dates.extend([before.date, record.date]),
+            Some(_) => {},
+        }
+    }
+    for record in &old.time_records {
+        if !new.time_records.iter().any(|r| r.id == record.id) {
+            dates.push(record.date);
+        }
+    }
+    dates.sort();
+    dates.dedup();
+    dates
+}
+
+impl Config {
+    /// Opens a locked period again, see Locks.unlock. Writes the locks
+    pub fn unlock_period(&mut self, period: &str, reason: &str) -> Result<()> {
+        self.locks.unlock(period, reason)?;
+        self.locks.write()?;
+        self.git_commit(&format!("Unlock {}: {}", period, reason.trim()))
+    }
+
+    /// Error if a date lies in a locked period
+    pub fn check_unlocked(&self, date: NaiveDate) -> Result<()> {
+        if let Some(lock) = self.locks.locked(date) {
+            bail!("{} lies in the locked period {}. Unlock it first to change it", date, lock);
+        }
+        Ok(())
+    }
+}
+
+/// Lock status of a range for reports, e.g. `Locked: 2025-11 (submitted 2025-12-01 10:00)`
+pub fn lock_status(locks: &[PeriodLock]) -> String {
+    format!("Locked: {}", locks.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", "))
+}
+
+/// Prints the locked periods and the log of locks and unlocks
+pub fn print_locks(config: &Config) {
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    if config.locks.locks.is_empty() {
+        println!("No locked periods");
+    }
+    for lock in &config.locks.locks {
+        println!("{} {} - {}", lock, lock.from, lock.to);
+    }
+    if !config.locks.log.is_empty() {
+        println!("\nLog:");
+        for event in &config.locks.log {
+            println!("  {}", event);
+        }
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+}
+
+/// Menu to submit and lock periods, unlock them and list them
+pub fn locks_menu(config: &mut Config) -> Result<()> {
+
+    loop {
+        let options = vec!["Lock Period", "Unlock Period", "Show Locks", "Verify Seals", "Back"];
+        match Select::new("Locked Periods", options).prompt_skippable()? {
+            Some("Lock Period") => {
+                let Some(period) = Text::new("Month (2025-11) or week (2025-W45) to lock:")
+                    .with_validator(|input: &str| match parse_period(input) {
+                        Ok(_) => Ok(Validation::Valid),
+                        Err(msg) => Ok(Validation::Invalid(msg.into())),
+                    })
+                    .prompt_skippable()? else { continue };
+                match config.lock_period(&period) {
+                    Ok(lock) => println!("{} is locked and sealed", lock),
+                    Err(e) => println!("{}", e),
+                }
+            },
+            Some("Unlock Period") => {
+                let periods: Vec<String> = config.locks.locks.iter().map(|l| l.period.clone()).collect();
+                if periods.is_empty() {
+                    println!("No locked periods");
+                    continue;
+                }
+                let Some(period) = Select::new("Which period?", periods).prompt_skippable()? else { continue };
+                let Some(reason) = Text::new("Reason for unlocking:")
+                    .with_validator(|input: &str| if input.trim().is_empty() {
+                        Ok(Validation::Invalid("The reason is kept in the log and can't be empty".into()))
+                    } else {
+                        Ok(Validation::Valid)
+                    })
+                    .prompt_skippable()? else { continue };
+                config.unlock_period(&period, &reason)?;
+                println!("{} is unlocked", period);
+            },
+            Some("Show Locks") => print_locks(config),
+            Some("Verify Seals") => if let Err(e) = print_verification(config) {
+                println!("{}", e);
+            },
+            _ => break Ok(()),
+        }
+    }
+}
@@ -1,15 +1,28 @@
 use ptt_cli::ui::*;
+use ptt_cli::cli::{Cli, execute};
 use anyhow::{Result};
+use clap::Parser;
 use ptt_cli::models::Config;
+use ptt_cli::instance::{InstanceLock, INSTANCE_LOCK_FILE};
 
 fn main() -> Result<()>{
 
+    let cli = Cli::parse();
+
     let t_name = "data.json";
     let p_name = "projects.json";
-    let mut config = Config::build(t_name, p_name)?;
-    
-    if let Err(e) = run(&mut config) {
+    let s_name = "settings.json";
+    let lock = InstanceLock::acquire(&Config::get_local_config_path(INSTANCE_LOCK_FILE)?)?;
+    let mut config = Config::build(t_name, p_name, s_name)?;
+
+    let result = match cli.command {
+        Some(command) => execute(&mut config, command),
+        None => run(&mut config),
+    };
+
+    if let Err(e) = result {
         eprintln!("{e:?}");
+        drop(lock);
         std::process::exit(1);        
     }
 
@@ -1,22 +1,36 @@
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use inquire::Confirm;
 use serde::de::DeserializeOwned;
 use serde::{Serialize, Deserialize};
 // use crate::storage::*;
-use anyhow::{Context, Result};
+use crate::journal::{COMPACT_AFTER, Change, Journal, Snapshot, diff};
+use crate::undo::UndoStack;
+use crate::history::{record_revisions, Tombstones};
+use crate::locks::{Locks, changed_dates};
+use crate::seals::Seals;
+use crate::instance::DiskStamp;
+use crate::git::commit_message;
+use anyhow::{Context, Result, bail};
+use std::collections::BTreeMap;
 use std::path::{PathBuf};
 use std::{fs,env};
 
 
 /// A Time Record of a day. Summarizes start, end and pause of a worker as well as
 /// their activities for multiple projects
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TimeRecord {
+    /// Stable unique id. 0 means not assigned yet, see Config.assign_missing_ids
+    #[serde(default)]
+    pub id: u64,
     pub date: NaiveDate,
     pub start_time: NaiveTime,
     pub end_time: NaiveTime,
     pub pause_minutes: f64,
-    pub project_entries: Vec<ProjectEntry> // Can be initialized as an empty vec![]. Will be pushed with ProjectEntries!
+    pub project_entries: Vec<ProjectEntry>, // Can be initialized as an empty vec![]. Will be pushed with ProjectEntries!
+    /// Earlier values of the workday, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Revision>
 }
 
 impl TimeRecord{
@@ -42,10 +56,18 @@ impl TimeRecord{
     pub fn remaining_hours(&self) -> f64 {
         self.get_net_hours() - self.allocated_hours()
     }
+    /// Error if an entry of the day is billed, the day can't be overwritten or deleted then
+    pub fn check_unbilled(&self) -> Result<()> {
+        if let Some(entry) = self.project_entries.iter().find(|e| e.invoice.is_some()) {
+            bail!("{} has project entry #{} billed with invoice {} and can't be overwritten or deleted",
+                self.date, entry.id, entry.invoice.as_deref().unwrap_or_default());
+        }
+        Ok(())
+    }
     /// Prints the already allocated projects and time windows for a project
     pub fn print_already_recorded(&self) -> (){
         for entry in &self.project_entries {
-            println!("- {:?}, Allocated: {}", entry.project_name.code, entry.hours);
+            println!("- #{} {:?}, Allocated: {}", entry.id, entry.project_name.code, entry.hours);
         }
     }
     /// Checks, if there is already an entry for a TimeRecord
@@ -55,40 +77,433 @@ impl TimeRecord{
         self.project_entries.iter().any(|e| &e.project_name.code == project_code)
    
     }
+
+    /// Time slots of the entries, which only store hours. Entries are laid out one after
+    /// another from the start of the day, the pause goes before the first entry starting at noon or later.
+    /// Slots running past midnight end on the next day
+    /// Returns:
+    ///     Start and end of each entry, in the order of project_entries
+    pub fn entry_slots(&self) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        let noon = self.date.and_time(NaiveTime::from_hms_opt(12, 0, 0).expect("valid time"));
+        let mut cursor = self.date.and_time(self.start_time);
+        let mut paused = self.pause_minutes <= 0.0;
+        let mut slots = Vec::new();
+        for entry in &self.project_entries {
+            if !paused && cursor >= noon {
+                cursor += TimeDelta::minutes((self.pause_minutes * 60.0).round() as i64);
+                paused = true;
+            }
+            let end = cursor + TimeDelta::minutes((entry.hours * 60.0).round() as i64);
+            slots.push((cursor, end));
+            cursor = end;
+        }
+        slots
+    }
+
+    /// Checks the hours of a new entry against the hours still free on this day
+    pub fn validate_hours(&self, hours: f64) -> Result<(), String> {
+        if hours <= 0.0 {
+            Err(String::from("Please assign more than zero hours!"))
+        } else if hours > self.remaining_hours() {
+            Err(format!("You have assigned too much time, only {} hours are left on {}", self.remaining_hours(), self.date))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks a pause in hours. Pauses are recorded in quarter hours
+    pub fn validate_pause(pause: f64) -> Result<(), String> {
+        if pause < 0.0 || (pause * 4.0).fract() != 0.0 {
+            Err(String::from("Pause must be in 0.25 hour increments"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Adds an entry following the duplicate policy. The entry needs its id already.
+    /// Returns:
+    ///     The id of the entry it was merged into, None if it was added
+    pub fn insert_entry(&mut self, entry: ProjectEntry, policy: DuplicatePolicy) -> Result<Option<u64>> {
+        let code = &entry.project_name.code;
+        if policy == DuplicatePolicy::Reject && self.prohibit_duplicate_entry(code) {
+            bail!("An entry for project {} already exists on {}", code, self.date);
+        }
+
+        // Billed entries are closed, so only an unbilled entry takes the merge
+        if policy == DuplicatePolicy::Merge
+            && let Some(existing) = self.project_entries
+                .iter_mut()
+                .find(|e| &e.project_name.code == code && e.invoice.is_none()) {
+            existing.merge(entry);
+            return Ok(Some(existing.id));
+        }
+
+        self.project_entries.push(entry);
+        Ok(None)
+    }
+}
+
+/// Longest activity accepted for a project entry
+pub const MAX_ACTIVITY_LEN: usize = 500;
+
+/// A changed value of a workday or entry, formatted for reading
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: String,
+    pub to: String
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (self.from.is_empty(), self.to.is_empty()) {
+            (true, _) => write!(f, "{} added: {}", self.field, self.to),
+            (_, true) => write!(f, "{} removed: {}", self.field, self.from),
+            _ => write!(f, "{}: {} -> {}", self.field, self.from, self.to),
+        }
+    }
+}
+
+/// One change of a workday or entry: when, what and why, see history.rs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub at: NaiveDateTime,
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub changes: Vec<FieldChange>
 }
 
 
 /// Struct to store the project, the time frame and the type of activity
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ProjectEntry {
+    /// Stable unique id. 0 means not assigned yet, see Config.assign_missing_ids
+    #[serde(default)]
+    pub id: u64,
     pub project_name: Project,
     pub hours: f64,
-    pub activity: String
+    pub activity: String,
+    /// Tags like meeting, development or review. Stored without the leading '#'
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Number of the invoice the entry was billed with. None while unbilled
+    #[serde(default)]
+    pub invoice: Option<String>,
+    /// Earlier values of the entry, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Revision>
+}
+
+impl ProjectEntry {
+    /// Collects the `#tags` written in an activity. Tags are lowercased and deduplicated
+    pub fn extract_tags(activity: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for word in activity.split_whitespace() {
+            if let Some(tag) = word.strip_prefix('#') {
+                let tag: String = tag
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                    .collect::<String>()
+                    .to_lowercase();
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+        tags
+    }
+
+    /// Short one line description used in selections: `#id CODE (hours h) activity`
+    pub fn label(&self) -> String {
+        let mut activity: String = self.activity.chars().take(40).collect();
+        if activity.len() < self.activity.len() {
+            activity.push_str("...");
+        }
+        format!("#{} {} ({} h) {}", self.id, self.project_name.code, self.hours, activity)
+    }
+
+    /// Adds hours, activity and tags of another entry of the same project
+    pub fn merge(&mut self, other: ProjectEntry) {
+        self.hours += other.hours;
+        if self.activity.is_empty() {
+            self.activity = other.activity;
+        } else if !other.activity.is_empty() {
+            self.activity = format!("{}; {}", self.activity, other.activity);
+        }
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    /// Checks an activity text against the rules of the prompts
+    pub fn validate_activity(activity: &str) -> Result<(), String> {
+        if activity.len() <= MAX_ACTIVITY_LEN {
+            Ok(())
+        } else {
+            Err(format!("Activity should be no longer than {} characters!", MAX_ACTIVITY_LEN))
+        }
+    }
+
+    /// Checks if the entry carries a tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
 }
 
 /// Struct to store Projects of a user in memory (json)
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Non-project time (admin, training, vacation...) is stored the same way, flagged as internal
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Project {
     pub code: String, // Should be changed to &str
-    pub allocation: f64
+    pub allocation: f64,
+    /// Internal, non-billable category instead of a customer project
+    #[serde(default)]
+    pub internal: bool,
+    /// Hourly rate for invoices
+    #[serde(default)]
+    pub rate: Option<f64>,
+    /// Client the project is billed to
+    #[serde(default)]
+    pub client: Option<String>
 }
 
+/// Categories seeded into a catalog without any internal category, or used by entries
+pub const DEFAULT_CATEGORIES: [&str; 2] = ["INT", "Vacation"];
+
 impl Project {
     pub fn check_empty(&self) -> bool {
         return self.code.is_empty();
     }
+
+    /// Creates an internal, non-billable category
+    pub fn category(code: &str) -> Project {
+        Project { code: code.to_string(), allocation: 0.0, internal: true, rate: None, client: None }
+    }
+}
+
+/// What happens when a second entry for the same project is added to a day
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Keep both entries
+    #[default]
+    Allow,
+    /// Add hours, activity and tags to the existing entry
+    Merge,
+    /// Refuse the new entry
+    Reject
+}
+
+impl std::fmt::Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DuplicatePolicy::Allow => write!(f, "Allow several entries"),
+            DuplicatePolicy::Merge => write!(f, "Merge into the existing entry"),
+            DuplicatePolicy::Reject => write!(f, "Reject the new entry"),
+        }
+    }
+}
+
+/// Format of exported CSV files. German Excel expects ';' and ','
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct CsvFormat {
+    pub delimiter: char,
+    pub decimal_separator: char
+}
+
+impl Default for CsvFormat {
+    fn default() -> CsvFormat {
+        CsvFormat { delimiter: ',', decimal_separator: '.' }
+    }
+}
+
+impl CsvFormat {
+    /// Formats a number with two decimals and the configured decimal separator
+    pub fn number(&self, value: f64) -> String {
+        let formatted = format!("{:.2}", value);
+        if self.decimal_separator == '.' {
+            formatted
+        } else {
+            formatted.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+}
+
+/// Header of the printed monthly timesheet
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct TimesheetHeader {
+    pub name: String,
+    pub employee_number: String,
+    pub customer: String
+}
+
+/// Issuer data and numbering of invoices
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct InvoiceSettings {
+    /// Name and address of the issuer, one line each
+    pub issuer: Vec<String>,
+    pub vat_percent: f64,
+    pub currency: String,
+    pub prefix: String,
+    /// Number of the next invoice, without prefix
+    pub next_number: u32
+}
+
+impl Default for InvoiceSettings {
+    fn default() -> InvoiceSettings {
+        InvoiceSettings { issuer: vec![], vat_percent: 19.0, currency: String::from("EUR"), prefix: String::from("INV-"), next_number: 1 }
+    }
+}
+
+impl InvoiceSettings {
+    /// Formatted number of the next invoice, e.g. INV-0007
+    pub fn next_invoice_number(&self) -> String {
+        format!("{}{:04}", self.prefix, self.next_number)
+    }
+}
+
+/// Optional git repository in the data directory, see git.rs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct GitSettings {
+    /// Commit the data files with every save. The remote is set per machine, see Config.git_remote
+    pub enabled: bool
+}
+
+/// Maps calendar events to a project by a keyword in their summary or description and/or
+/// by their organizer. All given conditions must match, case is ignored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CalendarRule {
+    #[serde(default)]
+    pub keyword: Option<String>,
+    #[serde(default)]
+    pub organizer: Option<String>,
+    pub project: String
+}
+
+impl CalendarRule {
+    pub fn matches(&self, text: &str, organizer: &str) -> bool {
+        let contains = |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+        (self.keyword.is_some() || self.organizer.is_some())
+            && self.keyword.as_ref().is_none_or(|k| contains(text, k))
+            && self.organizer.as_ref().is_none_or(|o| contains(organizer, o))
+    }
+}
+
+impl std::fmt::Display for CalendarRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(keyword) = &self.keyword {
+            conditions.push(format!("keyword '{}'", keyword));
+        }
+        if let Some(organizer) = &self.organizer {
+            conditions.push(format!("organizer '{}'", organizer));
+        }
+        write!(f, "{} -> {}", conditions.join(" and "), self.project)
+    }
+}
+
+/// User settings and small catalogs, stored as a single object in settings.json
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Managed list of tags that can be attached to project entries
+    pub tags: Vec<String>,
+    /// Policy for several entries of the same project on one day
+    pub duplicate_policy: DuplicatePolicy,
+    /// Delimiter and decimal separator of CSV exports
+    pub csv: CsvFormat,
+    /// Header of the PDF timesheet
+    pub timesheet: TimesheetHeader,
+    /// Issuer, VAT and numbering of invoices
+    pub invoice: InvoiceSettings,
+    /// Project codes for project or client names of other time trackers, remembered from imports
+    pub project_mapping: BTreeMap<String, String>,
+    /// Rules mapping calendar events to projects, checked in order
+    pub calendar_rules: Vec<CalendarRule>,
+    /// Number of saves that can be undone, kept in undo.json
+    pub undo_depth: usize,
+    /// Commits of the data files and their remote
+    pub git: GitSettings,
+    /// Highest workday id handed out so far, ids of deleted workdays aren't used again
+    pub last_record_id: u64,
+    /// Highest entry id handed out so far
+    pub last_entry_id: u64,
+    /// Domain part of the UIDs of exported calendars, made up on the first export
+    pub uid_domain: Option<String>
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            tags: vec![],
+            duplicate_policy: DuplicatePolicy::default(),
+            csv: CsvFormat::default(),
+            timesheet: TimesheetHeader::default(),
+            invoice: InvoiceSettings::default(),
+            project_mapping: BTreeMap::new(),
+            calendar_rules: vec![],
+            undo_depth: 20,
+            git: GitSettings::default(),
+            last_record_id: 0,
+            last_entry_id: 0,
+            uid_domain: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Adds tags to the managed tag list. Already known tags are ignored.
+    /// Does not save, call Config.save after usage
+    pub fn add_tags(&mut self, tags: &[String]) {
+        for tag in tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+        self.tags.sort();
+    }
 }
 
 /// Storage of timeRecords and ProjectRecords for the CLI
 /// Type:
 ///     time_records: Vec<TimeRecord>
 ///     project_records: Vec<Project>
+///     settings: Settings
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub time_records: Vec<TimeRecord>,
     pub project_records: Vec<Project>,
+    pub settings: Settings,
     pub t_path: PathBuf,
-    pub p_path: PathBuf
+    pub p_path: PathBuf,
+    pub s_path: PathBuf,
+    /// Journal the saves append to, see journal.rs
+    #[serde(skip)]
+    pub journal: Journal,
+    /// Saves that can be undone and redone, see undo.rs
+    #[serde(skip)]
+    pub undo: UndoStack,
+    /// Reason for the changes of the next save, kept in the history
+    #[serde(skip)]
+    pub reason: Option<String>,
+    /// Submitted periods that can't be changed, see locks.rs
+    #[serde(skip)]
+    pub locks: Locks,
+    /// Hash chain over the locked periods, see seals.rs
+    #[serde(skip)]
+    pub seals: Seals,
+    /// Deleted workdays with their history, see history.rs
+    #[serde(skip)]
+    pub tombstones: Tombstones,
+    /// Stamp of the files at the last load or save, None if not loaded from disk. See instance.rs
+    #[serde(skip)]
+    pub on_disk: Option<DiskStamp>
     //ADD t_path and p_path as Str
 }
 
@@ -96,24 +511,143 @@ pub struct Config {
 impl Config{
 
     /// Builds the initial Config struct at the start of the programm
-    pub fn build(t_name: &str, p_name: &str) -> Result<Config> {
+    pub fn build(t_name: &str, p_name: &str, s_name: &str) -> Result<Config> {
         // Intended to load all the data.
 
         //let time_records: Vec<TimeRecord> = load_data(t_name);
         //let project_records: Vec<Project> = load_data(p_name);
         let time_records: Vec<TimeRecord> = Config::load(t_name)?;
         let project_records: Vec<Project> = Config::load(p_name)?;
+        let settings: Settings = Config::load_settings(s_name)?;
 
         let t_path = Config::get_local_config_path(t_name)?;
         let p_path = Config::get_local_config_path(p_name)?;
+        let s_path = Config::get_local_config_path(s_name)?;
+
+        let journal = Journal::open(&t_path, Snapshot { time_records, project_records, settings })?;
+        let Snapshot { time_records, project_records, settings } = journal.base.clone();
+
+        let undo = UndoStack::open(&t_path)?;
+        let locks = Locks::open(&t_path)?;
+        let seals = Seals::open(&t_path)?;
+        let tombstones = Tombstones::open(&t_path)?;
+
+        let mut config = Config{time_records, project_records, settings, t_path, p_path, s_path, journal, undo, reason: None, locks, seals, tombstones, on_disk: None};
+        config.on_disk = Some(config.disk_stamp());
+        config.assign_missing_ids();
+        if config.seed_categories() {
+            config.persist()?;
+        }
+        Ok(config)
+    }
+
+    /// Gives every record and entry without an id (data from older versions) a new one.
+    /// The ids are persisted with the next save
+    pub fn assign_missing_ids(&mut self) {
+        let mut next_record = self.next_record_id();
+        let mut next_entry = self.next_entry_id();
+
+        for record in self.time_records.iter_mut() {
+            if record.id == 0 {
+                record.id = next_record;
+                next_record += 1;
+            }
+            for entry in record.project_entries.iter_mut() {
+                if entry.id == 0 {
+                    entry.id = next_entry;
+                    next_entry += 1;
+                }
+            }
+        }
+    }
+
+    /// Next free id for a TimeRecord. Ids are never handed out twice, see Settings.last_record_id
+    pub fn next_record_id(&self) -> u64 {
+        self.time_records.iter().map(|r| r.id).max().unwrap_or(0).max(self.settings.last_record_id) + 1
+    }
+
+    /// Next free id for a ProjectEntry. Entry ids are unique over all records and never handed out twice
+    pub fn next_entry_id(&self) -> u64 {
+        self.time_records
+            .iter()
+            .flat_map(|r| r.project_entries.iter())
+            .map(|e| e.id)
+            .max()
+            .unwrap_or(0)
+            .max(self.settings.last_entry_id) + 1
+    }
+
+    /// Raises the counters of the settings to the ids in use, so they stay used after a delete
+    fn count_used_ids(&mut self) {
+        self.settings.last_record_id = self.next_record_id() - 1;
+        self.settings.last_entry_id = self.next_entry_id() - 1;
+    }
+
+    /// Searches a TimeRecord by its id
+    pub fn find_record(&self, id: u64) -> Option<&TimeRecord> {
+        self.time_records.iter().find(|r| r.id == id)
+    }
+
+    /// Searches a ProjectEntry by its id
+    /// Returns:
+    ///     The entry together with the record it belongs to
+    pub fn find_entry(&self, entry_id: u64) -> Option<(&TimeRecord, &ProjectEntry)> {
+        self.time_records
+            .iter()
+            .find_map(|r| r.project_entries.iter().find(|e| e.id == entry_id).map(|e| (r, e)))
+    }
+
+    /// Mutable access to a ProjectEntry by its id
+    pub fn find_entry_mut(&mut self, entry_id: u64) -> Option<&mut ProjectEntry> {
+        self.time_records
+            .iter_mut()
+            .flat_map(|r| r.project_entries.iter_mut())
+            .find(|e| e.id == entry_id)
+    }
+
+    /// Adds the default non-project categories to a catalog without any category, and a default
+    /// that entries still use, e.g. INT of catalogs from before categories were stored.
+    /// An existing project with a default code (the old hard-coded INT) is marked internal instead
+    /// Returns:
+    ///     Whether the catalog changed, Config::build saves it right away then
+    pub fn seed_categories(&mut self) -> bool {
+        let no_categories = !self.project_records.iter().any(|p| p.internal);
+        let mut seeded = false;
+        for code in DEFAULT_CATEGORIES {
+            let referenced = self.time_records.iter().any(|r| r.project_entries.iter().any(|e| e.project_name.code == code));
+            match self.project_records.iter_mut().find(|p| p.code == code) {
+                Some(project) if no_categories && !project.internal => project.internal = true,
+                None if no_categories || referenced => self.project_records.push(Project::category(code)),
+                _ => continue,
+            }
+            seeded = true;
+        }
+        seeded
+    }
+
+    /// Customer projects of the catalog
+    pub fn projects(&self) -> impl Iterator<Item = &Project> {
+        self.project_records.iter().filter(|p| !p.internal)
+    }
 
-        Ok(Config{time_records, project_records, t_path, p_path})
+    /// Internal, non-billable categories of the catalog
+    pub fn categories(&self) -> impl Iterator<Item = &Project> {
+        self.project_records.iter().filter(|p| p.internal)
+    }
+
+    /// Checks if a code belongs to an internal category. Unknown codes count as projects
+    pub fn is_internal(&self, code: &str) -> bool {
+        self.project_records.iter().any(|p| p.code == code && p.internal)
     }
 
     /// Adds a time Record to Config.time_records
     pub fn add_time_record(&mut self, new_record: &TimeRecord)-> Result<()>{
         //Call Save after usage
-        self.time_records.push(new_record.clone());
+        let mut new_record = new_record.clone();
+        if new_record.id == 0 {
+            new_record.id = self.next_record_id();
+        }
+        self.time_records.push(new_record);
         self.save()?;
         println!("Time Record added");
         Ok(())
@@ -131,28 +665,95 @@ impl Config{
     }
 
     /// Adds a ProjectEntry to my TimeRecord stored in my Config.time_records
+    /// Applies the duplicate policy of the settings if the project already has an entry that day
     pub fn add_project_entry(&mut self, date: NaiveDate, new_project_entry: ProjectEntry)-> Result<()>{
+        let policy = self.settings.duplicate_policy;
+        let next_entry_id = self.next_entry_id();
+
         if let Some(record) = self.time_records.iter_mut().find(|r| r.date == date) {
-            /*
-            if record.prohibit_duplicate_entry(&new_project_entry.project_name.code){
-                println!("A record for this project already exists");
-                return Err(anyhow!("Duplicate entry for record {:#?}", record));
-            } else {
-                record.project_entries.push(new_project_entry);
-                self.save()?;
-                println!("Project entry added!");
-                return Ok(());
+            let mut new_project_entry = new_project_entry;
+            if new_project_entry.id == 0 {
+                new_project_entry.id = next_entry_id;
+            }
+            match record.insert_entry(new_project_entry, policy)? {
+                Some(merged_id) => {
+                    self.save()?;
+                    println!("Project entry merged into #{}!", merged_id);
+                },
+                None => {
+                    self.save()?;
+                    println!("Project entry added!");
+                }
             }
-            */
-
-            record.project_entries.push(new_project_entry);
-            self.save()?;
-            println!("Project entry added!");
             return Ok(());
         }
         return Ok(());
     }
 
+    /// Deletes a single ProjectEntry by its id
+    pub fn delete_project_entry(&mut self, entry_id: u64) -> Result<()> {
+        let record = self.time_records
+            .iter_mut()
+            .find(|r| r.project_entries.iter().any(|e| e.id == entry_id))
+            .with_context(|| format!("No project entry with id #{}", entry_id))?;
+
+        if let Some(lock) = self.locks.locked(record.date) {
+            bail!("Project entry #{} lies in the locked period {} and can't be deleted", entry_id, lock);
+        }
+        if let Some(invoice) = record.project_entries.iter().find(|e| e.id == entry_id).and_then(|e| e.invoice.as_ref()) {
+            bail!("Project entry #{} is billed with invoice {} and can't be deleted", entry_id, invoice);
+        }
+        record.project_entries.retain(|e| e.id != entry_id);
+        self.save()?;
+        println!("Project entry deleted");
+        Ok(())
+    }
+
+    /// Changes hours, activity and tags of a ProjectEntry by its id. Fields given as None stay.
+    /// Billed entries and entries in locked periods are refused
+    pub fn update_project_entry(&mut self, entry_id: u64, hours: Option<f64>, activity: Option<String>, tags: Option<Vec<String>>) -> Result<()> {
+        let (record, entry) = self.find_entry(entry_id)
+            .with_context(|| format!("No project entry with id #{}", entry_id))?;
+        self.check_unlocked(record.date)?;
+        if let Some(invoice) = &entry.invoice {
+            bail!("Project entry #{} is billed with invoice {} and can't be changed anymore", entry_id, invoice);
+        }
+        if let Some(hours) = hours {
+            let available = record.remaining_hours() + entry.hours;
+            if hours <= 0.0 || hours > available {
+                bail!("Hours must be above 0 and at most {} on {}", available, record.date);
+            }
+        }
+        if let Some(activity) = &activity {
+            ProjectEntry::validate_activity(activity).map_err(anyhow::Error::msg)?;
+        }
+        if let Some(tags) = &tags {
+            self.settings.add_tags(tags);
+        }
+
+        let entry = self.find_entry_mut(entry_id).expect("entry was found");
+        if let Some(hours) = hours {
+            entry.hours = hours;
+        }
+        if let Some(activity) = activity {
+            entry.activity = activity;
+        }
+        if let Some(tags) = tags {
+            entry.tags = tags;
+        }
+        self.save()?;
+        println!("Project entry #{} updated", entry_id);
+        Ok(())
+    }
+
+    /// Removes a tag from the managed list. Entries keep their tags
+    pub fn delete_tag(&mut self, tag: &str) -> Result<()> {
+        self.settings.tags.retain(|t| t != tag);
+        self.save()?;
+        println!("Tag deleted");
+        Ok(())
+    }
+
     /// Deletes a Project from Config.project_records
     pub fn delete_project(&mut self, to_delete: String) -> Result<()> {
 
@@ -167,13 +768,18 @@ impl Config{
         Ok(())
     }
 
-    pub fn delete_time_record(&mut self, date: NaiveDate) -> Result<()> {
+    pub fn delete_time_record(&mut self, id: u64) -> Result<()> {
+
+        let record = self.find_record(id)
+            .with_context(|| format!("No time record with id #{}", id))?;
+        let date = record.date;
+        self.check_unlocked(date).and(record.check_unbilled())?;
 
         let confirm = Confirm::new(&format!("Are you sure you want to delete the record for date: {}", date))
             .prompt()?;
 
         if matches!(confirm, true){
-            self.time_records.retain(|r| r.date != date);
+            self.time_records.retain(|r| r.id != id);
             self.save()?;
             println!("Time Record deleted");
             return Ok(());
@@ -215,24 +821,119 @@ impl Config{
 
     }
 
-    pub fn save(&self)-> Result<()> {
-        //let file_path = self.get_local_config_path();
-        
-        if let Ok(t_json) = serde_json::to_string_pretty(&self.time_records)
-            .context("Failed to seialize time_records") {
-                fs::write(&self.t_path, t_json)
-                    .with_context(|| format!("Failed to write TimeRecords JSON to {:#?}", &self.t_path))?;
-            }; 
-        if let Ok(p_json) = serde_json::to_string_pretty(&self.project_records)
-            .context("Failed to serialize projects") {
-                fs::write(&self.p_path, p_json)
-                    .with_context(|| format!("Failed to write Project Records JSON to {:#?}", &self.p_path))?;
-            };
-        
+    /// Loads the settings object. Missing or empty files give the default settings
+    pub fn load_settings(file_name: &str) -> Result<Settings> {
+
+        let p = Config::get_local_config_path(file_name)?;
+
+        if !p.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = fs::read_to_string(&p)
+            .with_context(|| format!("Failed to read path: {:#?}", p))?;
+
+        if contents.trim().is_empty() {
+            return Ok(Settings::default());
+        }
+
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to deserialze settings for path: {:#?}", p))
+    }
+
+    /// Records the previous values of changed workdays and entries, appends the changes since
+    /// the last save to the journal and remembers them for undo.
+    /// The data files are only rewritten when the journal is compacted, every COMPACT_AFTER lines
+    /// or with every save in git mode, which also commits them
+    pub fn save(&mut self) -> Result<()> {
+        self.merge_from_disk()?;
+        let base = self.journal.base.clone();
+        let reason = self.reason.take();
+        record_revisions(&base, self, reason);
+        let changes = self.persist()?;
+        if !changes.is_empty() && self.settings.undo_depth > 0 {
+            let undo = diff(&Snapshot::of(self), &base);
+            self.undo.push(undo, changes, self.settings.undo_depth);
+            self.undo.write()?;
+        } else if !changes.is_empty() && !self.undo.is_empty() {
+            // With undo turned off a save still can't be redone over
+            self.undo.limit(0);
+            self.undo.write()?;
+        }
         println!("Data saved sucessfully");
         Ok(())
     }
 
+    /// Writes the changes since the last save to the journal, without undo. Changes of workdays
+    /// in locked periods are refused and the data is reset to the last save.
+    /// If another ptt saved in the meantime, the changes are merged into its data first
+    /// Returns:
+    ///     The written changes
+    pub fn persist(&mut self) -> Result<Vec<Change>> {
+        self.merge_from_disk()?;
+        self.count_used_ids();
+        let current = Snapshot::of(self);
+        let locked = changed_dates(&self.journal.base, &current).into_iter().find(|d| self.locks.locked(*d).is_some());
+        if let Some(date) = locked {
+            let error = self.check_unlocked(date).unwrap_err();
+            let Snapshot { time_records, project_records, settings } = self.journal.base.clone();
+            (self.time_records, self.project_records, self.settings) = (time_records, project_records, settings);
+            self.tombstones = Tombstones::open(&self.t_path)?;
+            bail!("{}. Nothing was saved", error);
+        }
+        let changes = diff(&self.journal.base, &current);
+        if changes.iter().any(|c| matches!(c, Change::RecordDeleted { .. })) {
+            self.tombstones.write()?;
+        }
+        self.journal.append(changes.clone())?;
+        self.journal.base = current;
+        self.refresh_disk_stamp();
+
+        if self.journal.events >= COMPACT_AFTER || self.settings.git.enabled {
+            self.compact()?;
+        }
+        if !changes.is_empty() {
+            self.git_commit(&commit_message(&changes))?;
+        }
+        Ok(changes)
+    }
+
+    /// Writes the current state into the data files and moves the journal to its archive.
+    /// Workdays are sorted by date first, so the files give readable diffs
+    pub fn compact(&mut self) -> Result<()> {
+        self.merge_from_disk()?;
+        self.time_records.sort_by_key(|r| (r.date, r.id));
+        self.journal.base.time_records.sort_by_key(|r| (r.date, r.id));
+        self.save_files()?;
+        self.journal.archive()?;
+        self.refresh_disk_stamp();
+        Ok(())
+    }
+
+    /// Takes the files as written by this ptt, see Config.merge_from_disk
+    fn refresh_disk_stamp(&mut self) {
+        if self.on_disk.is_some() {
+            self.on_disk = Some(self.disk_stamp());
+        }
+    }
+
+    /// Rewrites the data files with the whole state
+    fn save_files(&self) -> Result<()> {
+        let t_json = serde_json::to_string_pretty(&self.time_records)
+            .context("Failed to seialize time_records")?;
+        fs::write(&self.t_path, t_json)
+            .with_context(|| format!("Failed to write TimeRecords JSON to {:#?}", &self.t_path))?;
+        let p_json = serde_json::to_string_pretty(&self.project_records)
+            .context("Failed to serialize projects")?;
+        fs::write(&self.p_path, p_json)
+            .with_context(|| format!("Failed to write Project Records JSON to {:#?}", &self.p_path))?;
+        let s_json = serde_json::to_string_pretty(&self.settings)
+            .context("Failed to serialize settings")?;
+        fs::write(&self.s_path, s_json)
+            .with_context(|| format!("Failed to write Settings JSON to {:#?}", &self.s_path))?;
+        Ok(())
+    }
+
     pub fn get_local_config_path(file_name: &str) -> Result<PathBuf> {
         let mut path = env::current_dir()
             .context("Failed to retrieve current dir!")?;
@@ -242,6 +943,64 @@ impl Config{
     }
 }
 
+/// Inclusive range of dates used to filter records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> DateRange {
+        DateRange { from, to }
+    }
+
+    /// Range covering every possible date
+    pub fn all() -> DateRange {
+        DateRange { from: NaiveDate::MIN, to: NaiveDate::MAX }
+    }
+
+    /// Range of a calendar year
+    pub fn year(year: i32) -> Option<DateRange> {
+        Some(DateRange {
+            from: NaiveDate::from_ymd_opt(year, 1, 1)?,
+            to: NaiveDate::from_ymd_opt(year, 12, 31)?,
+        })
+    }
+
+    /// Range of a quarter (1-4) of a year
+    pub fn quarter(year: i32, quarter: u32) -> Option<DateRange> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+        let from = NaiveDate::from_ymd_opt(year, (quarter - 1) * 3 + 1, 1)?;
+        let to = from.checked_add_months(chrono::Months::new(3))?.pred_opt()?;
+        Some(DateRange { from, to })
+    }
+
+    /// Range of a month of a year
+    pub fn month(year: i32, month: u32) -> Option<DateRange> {
+        let from = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let to = from.checked_add_months(chrono::Months::new(1))?.pred_opt()?;
+        Some(DateRange { from, to })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+}
+
+impl std::fmt::Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.from == NaiveDate::MIN, self.to == NaiveDate::MAX) {
+            (true, true) => write!(f, "all time"),
+            (true, false) => write!(f, "until {}", self.to),
+            (false, true) => write!(f, "since {}", self.from),
+            (false, false) => write!(f, "{} - {}", self.from, self.to),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MonthChoice {
     pub month_name: String,
@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::import::parse_import_time;
+use crate::trackers::{TrackedEntry, TrackerImport, is_known_name, tag_name};
+
+/// Heading of an Org file with what its clocks inherit
+#[derive(Debug, Clone)]
+struct Heading {
+    level: usize,
+    title: String,
+    tags: Vec<String>,
+    project: Option<String>
+}
+
+/// Splits a heading line like `** TODO [#A] Review :meeting:` into level, title and tags
+fn parse_heading(line: &str) -> Option<(usize, String, Vec<String>)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 {
+        return None;
+    }
+    let rest = line[level..].strip_prefix(' ')?;
+    let mut words: Vec<&str> = rest.split_whitespace().collect();
+
+    let mut tags = Vec::new();
+    if let Some(last) = words.last()
+        && last.len() > 1 && last.starts_with(':') && last.ends_with(':')
+    {
+        tags = last.trim_matches(':').split(':').filter(|t| !t.is_empty()).map(tag_name).collect();
+        words.pop();
+    }
+    if words.first().is_some_and(|w| ["TODO", "DONE"].contains(w)) {
+        words.remove(0);
+    }
+    if words.first().is_some_and(|w| w.starts_with("[#") && w.ends_with(']')) {
+        words.remove(0);
+    }
+    Some((level, words.join(" "), tags))
+}
+
+/// Parses an Org timestamp like `[2025-11-09 Sun 08:00]`
+fn org_time(value: &str) -> Result<NaiveDateTime, String> {
+    let inner = value.trim().trim_start_matches(['[', '<']).trim_end_matches([']', '>']);
+    let parts: Vec<&str> = inner.split_whitespace().collect();
+    let invalid = || format!("'{}' is not a timestamp like [2025-11-09 Sun 08:00]", value.trim());
+    let (Some(date), Some(time)) = (parts.first(), parts.last()) else {
+        return Err(invalid());
+    };
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| invalid())?;
+    Ok(date.and_time(parse_import_time(time).map_err(|_| invalid())?))
+}
+
+/// Reads the `CLOCK:` lines of an Org file. The project is the `PROJECT` property of the
+/// heading or one of its parents, else the first word of the heading if it has a project code,
+/// else the top level heading. The heading is the activity, its tags and the inherited ones the tags
+pub fn read_org(contents: &str, is_project: impl Fn(&str) -> bool) -> Result<Vec<TrackedEntry>> {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut in_properties = false;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        if let Some((level, title, tags)) = parse_heading(line) {
+            headings.retain(|h| h.level < level);
+            headings.push(Heading { level, title, tags, project: None });
+            in_properties = false;
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.eq_ignore_ascii_case(":PROPERTIES:") {
+            in_properties = true;
+            continue;
+        }
+        if trimmed.eq_ignore_ascii_case(":END:") {
+            in_properties = false;
+            continue;
+        }
+        if in_properties {
+            if let Some(value) = trimmed.strip_prefix(":PROJECT:").or_else(|| trimmed.strip_prefix(":project:"))
+                && let Some(heading) = headings.last_mut()
+            {
+                heading.project = Some(value.trim().to_string());
+            }
+            continue;
+        }
+
+        let Some(clock) = trimmed.strip_prefix("CLOCK:") else {
+            continue;
+        };
+        let Some(heading) = headings.last() else {
+            errors.push(format!("line {}: clock outside of a heading", line_number));
+            continue;
+        };
+        let Some((start, end)) = clock.split_once("--") else {
+            errors.push(format!("line {}: the clock is still running", line_number));
+            continue;
+        };
+        let end = end.split("=>").next().unwrap_or_default();
+        let (start, end) = match (org_time(start), org_time(end)) {
+            (Ok(start), Ok(end)) => (start, end),
+            (Err(msg), _) | (_, Err(msg)) => {
+                errors.push(format!("line {}: {}", line_number, msg));
+                continue;
+            }
+        };
+
+        let mut activity = heading.title.clone();
+        let project = match headings.iter().rev().find_map(|h| h.project.clone()) {
+            Some(project) => project,
+            None => match activity.split_once(' ') {
+                Some((first, rest)) if is_project(first) => {
+                    let project = first.to_string();
+                    activity = rest.to_string();
+                    project
+                },
+                _ if is_project(&activity) => std::mem::take(&mut activity),
+                _ => headings[0].title.clone(),
+            },
+        };
+        let mut tags: Vec<String> = Vec::new();
+        for tag in headings.iter().flat_map(|h| h.tags.iter()) {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        entries.push(TrackedEntry { start, end, project, client: String::new(), description: activity, tags });
+    }
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    Ok(entries)
+}
+
+/// Reads an Org file, with the names known to the import as projects
+pub fn read_org_file(config: &Config, options: &TrackerImport, path: &Path) -> Result<Vec<TrackedEntry>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {:#?}", path))?;
+    read_org(&contents, |word| is_known_name(config, options, word))
+}
+
+/// Formats a date and time as inactive Org timestamp like [2025-11-09 Sun 08:00]
+fn org_stamp(time: NaiveDateTime) -> String {
+    time.format("[%Y-%m-%d %a %H:%M]").to_string()
+}
+
+/// Entries of a date range as Org file with a heading per day and per entry. The clocks use
+/// the time slots of `TimeRecord::entry_slots`, the project code is the `PROJECT` property
+pub fn org_file(config: &Config, range: DateRange) -> String {
+    let mut records: Vec<&TimeRecord> = config.time_records.iter().filter(|r| range.contains(r.date)).collect();
+    records.sort_by_key(|r| r.date);
+
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&format!("* {}\n", record.date.format("%Y-%m-%d %a")));
+        for (entry, (start, end)) in record.project_entries.iter().zip(record.entry_slots()) {
+            let activity = entry.activity.split_whitespace().collect::<Vec<_>>().join(" ");
+            let title = if activity.is_empty() { entry.project_name.code.clone() } else { activity };
+            // Org tags may not contain hyphens
+            let tags: Vec<String> = entry.tags.iter().map(|t| t.replace('-', "_")).collect();
+            let tags = if tags.is_empty() { String::new() } else { format!(" :{}:", tags.join(":")) };
+            let minutes = (entry.hours * 60.0).round() as i64;
+
+            out.push_str(&format!("** {}{}\n", title, tags));
+            out.push_str(&format!("   :PROPERTIES:\n   :PROJECT:  {}\n   :END:\n", entry.project_name.code));
+            out.push_str(&format!(
+                "   :LOGBOOK:\n   CLOCK: {}--{} => {:2}:{:02}\n   :END:\n",
+                org_stamp(start),
+                org_stamp(end),
+                minutes / 60,
+                minutes % 60
+            ));
+        }
+    }
+    out
+}
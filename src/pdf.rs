@@ -0,0 +1,210 @@
+/// Font of a text run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Widths of the Helvetica glyphs 32 (space) to 126 (~) in 1/1000 em
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Approximate width of a text in points
+pub fn text_width(text: &str, size: f64, font: Font) -> f64 {
+    let units: f64 = text
+        .chars()
+        .map(|c| match c as u32 {
+            32..=126 => HELVETICA_WIDTHS[(c as u32 - 32) as usize] as f64,
+            _ => 556.0,
+        })
+        .sum();
+    let bold_factor = if font == Font::Bold { 1.05 } else { 1.0 };
+    units * size / 1000.0 * bold_factor
+}
+
+/// Splits a text into lines no wider than `width`. Words longer than a line are cut
+pub fn wrap_text(text: &str, width: f64, size: f64, font: Font) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if text_width(&candidate, size, font) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Cut words that do not fit on a line of their own
+        let mut rest = word.to_string();
+        while text_width(&rest, size, font) > width && rest.chars().count() > 1 {
+            let mut cut = rest.chars().count() - 1;
+            while cut > 1 && text_width(&rest.chars().take(cut).collect::<String>(), size, font) > width {
+                cut -= 1;
+            }
+            lines.push(rest.chars().take(cut).collect());
+            rest = rest.chars().skip(cut).collect();
+        }
+        line = rest;
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Encodes a text as PDF string literal in WinAnsi (Latin-1 for the common characters)
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        let byte = match c {
+            '€' => 0x80,
+            '–' => 0x96,
+            '—' => 0x97,
+            '„' => 0x84,
+            '“' => 0x93,
+            '”' => 0x94,
+            c if (c as u32) < 256 => c as u32 as u8,
+            _ => b'?',
+        };
+        if matches!(byte, b'(' | b')' | b'\\') {
+            out.push(b'\\');
+        }
+        out.push(byte);
+    }
+    out.push(b')');
+    out
+}
+
+/// Minimal A4 PDF document with text and lines, written without external tools.
+/// Uses the standard Helvetica fonts, so no font files need to be embedded.
+/// The layout cursor moves from top to bottom and breaks pages
+#[derive(Debug, Clone)]
+pub struct PdfDocument {
+    pages: Vec<Vec<u8>>,
+    /// Vertical position of the next line, from the bottom of the page
+    pub y: f64
+}
+
+impl Default for PdfDocument {
+    fn default() -> PdfDocument {
+        PdfDocument::new()
+    }
+}
+
+impl PdfDocument {
+    pub const WIDTH: f64 = 595.0;
+    pub const HEIGHT: f64 = 842.0;
+    pub const MARGIN: f64 = 50.0;
+
+    /// Creates a document with a single empty page
+    pub fn new() -> PdfDocument {
+        PdfDocument { pages: vec![Vec::new()], y: Self::HEIGHT - Self::MARGIN }
+    }
+
+    /// Number of pages
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Starts a new page and moves the cursor to its top
+    pub fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = Self::HEIGHT - Self::MARGIN;
+    }
+
+    /// Reserves `height` points below the cursor, starting a new page if they do not fit.
+    /// Returns:
+    ///     The baseline for the reserved line
+    pub fn next_line(&mut self, height: f64) -> f64 {
+        if self.y - height < Self::MARGIN {
+            self.new_page();
+        }
+        self.y -= height;
+        self.y
+    }
+
+    fn content(&mut self) -> &mut Vec<u8> {
+        self.pages.last_mut().expect("a document always has a page")
+    }
+
+    /// Writes a text with its left edge at x
+    pub fn text(&mut self, x: f64, y: f64, size: f64, font: Font, text: &str) {
+        let mut op = format!("BT /{} {} Tf {:.2} {:.2} Td ", font.resource(), size, x, y).into_bytes();
+        op.extend(pdf_string(text));
+        op.extend(b" Tj ET\n");
+        self.content().extend(op);
+    }
+
+    /// Writes a text with its right edge at x
+    pub fn text_right(&mut self, x: f64, y: f64, size: f64, font: Font, text: &str) {
+        self.text(x - text_width(text, size, font), y, size, font, text);
+    }
+
+    /// Draws a line
+    pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, width: f64) {
+        let op = format!("{} w {:.2} {:.2} m {:.2} {:.2} l S\n", width, x1, y1, x2, y2);
+        self.content().extend(op.into_bytes());
+    }
+
+    /// Serializes the document
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let page_count = self.pages.len();
+        // Objects: 1 catalog, 2 pages, 3+4 fonts, then page and content object per page
+        let page_ids: Vec<usize> = (0..page_count).map(|i| 5 + i * 2).collect();
+
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+                page_count
+            ).into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec(),
+        ];
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                Self::WIDTH, Self::HEIGHT, id + 1
+            ).into_bytes());
+            let mut stream = format!("<< /Length {} >>\nstream\n", page.len()).into_bytes();
+            stream.extend(page);
+            stream.extend(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+            out.extend(object);
+            out.extend(b"\nendobj\n");
+        }
+
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+        }
+        out.extend(format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).into_bytes());
+        out
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+use inquire::{Select, Text};
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::models::*;
+
+/// How matching entries are grouped for totals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Day,
+    Week,
+    Month,
+    Year,
+    Project,
+    Tag
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<GroupBy> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(GroupBy::Day),
+            "week" => Ok(GroupBy::Week),
+            "month" => Ok(GroupBy::Month),
+            "year" => Ok(GroupBy::Year),
+            "project" => Ok(GroupBy::Project),
+            "tag" => Ok(GroupBy::Tag),
+            other => Err(anyhow!("Unknown grouping '{}'. Use day, week, month, year, project or tag", other)),
+        }
+    }
+}
+
+/// A project entry matched by a query, together with its record
+#[derive(Debug, Clone, Copy)]
+pub struct QueryHit<'a> {
+    pub record: &'a TimeRecord,
+    pub entry: &'a ProjectEntry
+}
+
+/// Filter and grouping over the project entries of the time records.
+///
+/// Written as space separated `key:value` terms, e.g.
+/// `project:NLS tag:meeting since:2025-10-01 until:2025-12-31 group:week`
+///
+/// - `project:CODE` (repeatable or comma separated, any of them matches)
+/// - `tag:NAME` (repeatable, all of them must match)
+/// - `since:YYYY-MM-DD`, `until:YYYY-MM-DD` (inclusive)
+/// - `month:YYYY-MM`, `year:YYYY`
+/// - `is:internal`, `is:billable`
+/// - `group:day|week|month|year|project|tag`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub projects: Vec<String>,
+    pub tags: Vec<String>,
+    pub range: DateRange,
+    pub internal: Option<bool>,
+    pub group: Option<GroupBy>
+}
+
+impl Default for Query {
+    fn default() -> Query {
+        Query { projects: vec![], tags: vec![], range: DateRange::all(), internal: None, group: None }
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("'{}' is not a date like 2025-10-01", value))
+}
+
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Query> {
+        let mut query = Query::default();
+
+        for term in s.split_whitespace() {
+            let (key, value) = term
+                .split_once(':')
+                .with_context(|| format!("'{}' is not a key:value term", term))?;
+            if value.is_empty() {
+                bail!("Missing value for '{}'", key);
+            }
+
+            match key.to_lowercase().as_str() {
+                "project" => query.projects.extend(value.split(',').map(String::from)),
+                "tag" => query.tags.push(value.trim_start_matches('#').to_lowercase()),
+                "since" | "from" => query.range.from = parse_date(value)?,
+                "until" | "to" => query.range.to = parse_date(value)?,
+                "month" => {
+                    let (year, month) = value
+                        .split_once('-')
+                        .and_then(|(y, m)| Some((y.parse().ok()?, m.parse().ok()?)))
+                        .with_context(|| format!("'{}' is not a month like 2025-11", value))?;
+                    query.range = DateRange::month(year, month)
+                        .with_context(|| format!("'{}' is not a valid month", value))?;
+                },
+                "year" => {
+                    let year = value.parse().with_context(|| format!("'{}' is not a year", value))?;
+                    query.range = DateRange::year(year)
+                        .with_context(|| format!("'{}' is not a valid year", value))?;
+                },
+                "is" => query.internal = match value.to_lowercase().as_str() {
+                    "internal" => Some(true),
+                    "billable" | "project" => Some(false),
+                    other => bail!("Unknown value is:{}. Use is:internal or is:billable", other),
+                },
+                "group" => query.group = Some(value.parse()?),
+                other => bail!("Unknown query key '{}'", other),
+            }
+        }
+
+        if query.range.from > query.range.to {
+            bail!("'since' lies after 'until'");
+        }
+        Ok(query)
+    }
+}
+
+impl Query {
+    /// Query for every entry in a date range
+    pub fn for_range(range: DateRange) -> Query {
+        Query { range, ..Query::default() }
+    }
+
+    /// Sets the grouping of the totals
+    pub fn grouped_by(mut self, group: GroupBy) -> Query {
+        self.group = Some(group);
+        self
+    }
+
+    /// Checks a single entry of a record against the filters
+    pub fn matches(&self, config: &Config, record: &TimeRecord, entry: &ProjectEntry) -> bool {
+        self.range.contains(record.date)
+            && (self.projects.is_empty() || self.projects.contains(&entry.project_name.code))
+            && self.tags.iter().all(|t| entry.has_tag(t))
+            && self.internal.is_none_or(|internal| config.is_internal(&entry.project_name.code) == internal)
+    }
+
+    /// All matching entries, sorted by date
+    pub fn entries<'a>(&self, config: &'a Config) -> Vec<QueryHit<'a>> {
+        let mut hits: Vec<QueryHit> = config.time_records
+            .iter()
+            .filter(|r| self.range.contains(r.date))
+            .flat_map(|r| r.project_entries.iter().map(move |e| QueryHit { record: r, entry: e }))
+            .filter(|hit| self.matches(config, hit.record, hit.entry))
+            .collect();
+        hits.sort_by_key(|hit| (hit.record.date, hit.entry.id));
+        hits
+    }
+
+    /// Keys a hit is counted for. Entries with several tags count for each of them
+    fn group_keys(&self, hit: &QueryHit) -> Vec<String> {
+        let date = hit.record.date;
+        match self.group {
+            None => vec![String::from("Total")],
+            Some(GroupBy::Day) => vec![date.to_string()],
+            Some(GroupBy::Week) => vec![format!("{}-W{:02}", date.iso_week().year(), date.iso_week().week())],
+            Some(GroupBy::Month) => vec![date.format("%Y-%m").to_string()],
+            Some(GroupBy::Year) => vec![date.year().to_string()],
+            Some(GroupBy::Project) => vec![hit.entry.project_name.code.clone()],
+            Some(GroupBy::Tag) if hit.entry.tags.is_empty() => vec![String::from(crate::reports::UNTAGGED)],
+            Some(GroupBy::Tag) => hit.entry.tags.clone(),
+        }
+    }
+
+    /// Sums up the hours of the matching entries per group, sorted by group key
+    pub fn totals(&self, config: &Config) -> BTreeMap<String, f64> {
+        let mut totals = BTreeMap::new();
+        for hit in self.entries(config) {
+            for key in self.group_keys(&hit) {
+                *totals.entry(key).or_insert(0.0) += hit.entry.hours;
+            }
+        }
+        totals
+    }
+}
+
+/// Prints the totals of a query
+pub fn print_query_totals(query: &Query, config: &Config) {
+    let totals = query.totals(config);
+    if totals.is_empty() {
+        println!("No matching entries");
+        return;
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    for (key, hours) in &totals {
+        println!("{:<20} {:>8.2}", key, hours);
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+}
+
+/// Prints the entries matched by a query
+pub fn print_query_entries(query: &Query, config: &Config) {
+    let hits = query.entries(config);
+    if hits.is_empty() {
+        println!("No matching entries");
+        return;
+    }
+    for hit in &hits {
+        println!("{}  {}", hit.record.date, hit.entry.label());
+    }
+    println!("{} entries, {:.2} hours", hits.len(), hits.iter().map(|h| h.entry.hours).sum::<f64>());
+}
+
+/// Lets the user type a query and shows its totals or entries
+pub fn query_report(config: &Config) -> Result<()> {
+
+    let query = loop {
+        let Some(input) = Text::new("Query:")
+            .with_help_message("e.g. project:NLS tag:meeting since:2025-10-01 until:2025-12-31 group:week")
+            .prompt_skippable()? else {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(());
+        };
+        match input.parse::<Query>() {
+            Ok(query) => break query,
+            Err(e) => println!("{}", e),
+        }
+    };
+
+    match Select::new("What would you like to see?", vec!["Totals", "Entries"]).prompt_skippable()? {
+        Some("Totals") => print_query_totals(&query, config),
+        Some(_) => print_query_entries(&query, config),
+        None => println!("Operation cancelled. Returning to main..."),
+    }
+    Ok(())
+}
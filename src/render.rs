@@ -0,0 +1,233 @@
+use std::path::Path;
+
+use chrono::{Datelike, Local};
+use inquire::{Select, Text};
+use anyhow::Result;
+
+use crate::models::*;
+use crate::reports::MonthlyReport;
+use crate::locks::lock_status;
+use crate::export::write_output;
+use crate::utils::{choose_month, choose_year};
+
+/// Output format of a rendered report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Plain text like in the menus
+    Text,
+    /// Markdown tables, e.g. for wikis
+    Markdown,
+    /// Standalone HTML page, e.g. for emails
+    Html
+}
+
+impl OutputFormat {
+    /// Usual file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Html => "html",
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "Text"),
+            OutputFormat::Markdown => write!(f, "Markdown"),
+            OutputFormat::Html => write!(f, "HTML"),
+        }
+    }
+}
+
+/// Renders a monthly report in the given format
+pub fn render_monthly(report: &MonthlyReport, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => monthly_text(report),
+        OutputFormat::Markdown => monthly_markdown(report),
+        OutputFormat::Html => monthly_html(report),
+    }
+}
+
+/// Group headings with their rows. Empty groups are skipped
+fn groups(report: &MonthlyReport) -> Vec<(&'static str, &[(String, f64)])> {
+    [("Projects", report.projects.as_slice()), ("Internal (non-billable)", report.internal.as_slice())]
+        .into_iter()
+        .filter(|(_, rows)| !rows.is_empty())
+        .collect()
+}
+
+fn monthly_text(report: &MonthlyReport) -> String {
+    let mut out = format!("Hour for {}/{}\n", report.month, report.year);
+    if !report.locks.is_empty() {
+        out.push_str(&format!("{}\n", lock_status(&report.locks)));
+    }
+    for (group, rows) in groups(report) {
+        out.push_str(&format!("=== {} ===\n", group));
+        for (project, hours) in rows {
+            out.push_str("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++\n");
+            out.push_str(&format!("Project: {}\n", project));
+            out.push_str(&format!("Assigned hours this month: {}\n", hours));
+            out.push_str("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++\n");
+            out.push_str("\n\n");
+        }
+        out.push_str(&format!("Total {}: {}\n\n", group, rows.iter().map(|(_, h)| h).sum::<f64>()));
+    }
+    out
+}
+
+/// Escapes text for a Markdown table cell
+fn md_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn monthly_markdown(report: &MonthlyReport) -> String {
+    let mut out = format!("# Hours for {}\n\n", report.title());
+    if !report.locks.is_empty() {
+        out.push_str(&format!("{}\n\n", md_escape(&lock_status(&report.locks))));
+    }
+    out.push_str("## Totals\n\n");
+
+    out.push_str("| Project | Group | Hours |\n|---|---|---:|\n");
+    for (group, rows) in groups(report) {
+        for (project, hours) in rows {
+            out.push_str(&format!("| {} | {} | {:.2} |\n", md_escape(project), group, hours));
+        }
+    }
+    out.push_str(&format!("| **Total** | | **{:.2}** |\n\n", report.total()));
+
+    out.push_str("## Days\n\n| Date | Start | End | Pause | Net hours | Allocated |\n|---|---|---|---:|---:|---:|\n");
+    for day in &report.days {
+        out.push_str(&format!(
+            "| {} | {} | {} | {:.2} | {:.2} | {:.2} |\n",
+            day.date.format("%a %Y-%m-%d"),
+            day.start_time.format("%H:%M"),
+            day.end_time.format("%H:%M"),
+            day.pause_minutes,
+            day.get_net_hours(),
+            day.allocated_hours()
+        ));
+    }
+    out.push_str(&format!("| **Total** | | | | **{:.2}** | **{:.2}** |\n\n", report.net_total(), report.total()));
+
+    out.push_str("## Activities\n\n");
+    for day in report.days.iter().filter(|d| !d.project_entries.is_empty()) {
+        out.push_str(&format!("### {}\n\n", day.date.format("%a %Y-%m-%d")));
+        for entry in &day.project_entries {
+            out.push_str(&format!("- **{}** ({:.2} h): {}\n", md_escape(&entry.project_name.code), entry.hours, md_escape(&entry.activity)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Escapes text for HTML
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Wraps a body into a standalone HTML page with a small print friendly style
+pub fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; color: #222; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+         th, td {{ border: 1px solid #bbb; padding: 0.3em 0.7em; text-align: left; }}\n\
+         td.num, th.num {{ text-align: right; }}\n\
+         tr.total td {{ font-weight: bold; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        html_escape(title),
+        body
+    )
+}
+
+fn monthly_html(report: &MonthlyReport) -> String {
+    let title = format!("Hours for {}", report.title());
+    let mut body = format!("<h1>{}</h1>\n", html_escape(&title));
+    if !report.locks.is_empty() {
+        body.push_str(&format!("<p>{}</p>\n", html_escape(&lock_status(&report.locks))));
+    }
+    body.push_str("<h2>Totals</h2>\n<table>\n<tr><th>Project</th><th>Group</th><th class=\"num\">Hours</th></tr>\n");
+
+    for (group, rows) in groups(report) {
+        for (project, hours) in rows {
+            body.push_str(&format!("<tr><td>{}</td><td>{}</td><td class=\"num\">{:.2}</td></tr>\n", html_escape(project), html_escape(group), hours));
+        }
+    }
+    body.push_str(&format!("<tr class=\"total\"><td>Total</td><td></td><td class=\"num\">{:.2}</td></tr>\n</table>\n", report.total()));
+
+    body.push_str("<h2>Days</h2>\n<table>\n<tr><th>Date</th><th>Start</th><th>End</th><th class=\"num\">Pause</th><th class=\"num\">Net hours</th><th class=\"num\">Allocated</th></tr>\n");
+    for day in &report.days {
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td></tr>\n",
+            day.date.format("%a %Y-%m-%d"),
+            day.start_time.format("%H:%M"),
+            day.end_time.format("%H:%M"),
+            day.pause_minutes,
+            day.get_net_hours(),
+            day.allocated_hours()
+        ));
+    }
+    body.push_str(&format!(
+        "<tr class=\"total\"><td>Total</td><td></td><td></td><td></td><td class=\"num\">{:.2}</td><td class=\"num\">{:.2}</td></tr>\n</table>\n",
+        report.net_total(),
+        report.total()
+    ));
+
+    body.push_str("<h2>Activities</h2>\n");
+    for day in report.days.iter().filter(|d| !d.project_entries.is_empty()) {
+        body.push_str(&format!("<h3>{}</h3>\n<ul>\n", day.date.format("%a %Y-%m-%d")));
+        for entry in &day.project_entries {
+            body.push_str(&format!(
+                "<li><strong>{}</strong> ({:.2} h): {}</li>\n",
+                html_escape(&entry.project_name.code),
+                entry.hours,
+                html_escape(&entry.activity)
+            ));
+        }
+        body.push_str("</ul>\n");
+    }
+
+    html_page(&title, &body)
+}
+
+/// Menu entry to save a monthly report as Markdown or HTML file
+pub fn render_monthly_menu(config: &Config) -> Result<()> {
+
+    let Some(year) = choose_year(config, "Please choose a year for the report")? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(month) = choose_month(config, "Please choose a month for the report", year)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let formats = vec![OutputFormat::Markdown, OutputFormat::Html, OutputFormat::Text];
+    let Some(format) = Select::new("Which format?", formats).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let default_name = format!("report-{}-{:02}.{}", year, month.month_number, format.extension());
+    let Some(path) = Text::new("File name:")
+        .with_default(&default_name)
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let report = MonthlyReport::build(config, year, month.month_number)?;
+    write_output(&render_monthly(&report, format), Some(Path::new(&path)))
+}
+
+/// Current year and month
+pub fn current_month() -> (i32, u32) {
+    let today = Local::now().date_naive();
+    (today.year(), today.month())
+}
@@ -0,0 +1,441 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
+use inquire::{DateSelect, Select};
+use anyhow::Result;
+
+use crate::models::*;
+use crate::query::{GroupBy, Query, QueryHit};
+use crate::locks::PeriodLock;
+use crate::utils::{ProjectHours, choose_project, choose_year, filter_time_record_totals, split_internal_totals};
+
+/// Label used for project entries without any tag
+pub const UNTAGGED: &str = "(untagged)";
+
+/// Iterates over every project entry of a year together with the date of its record
+fn entries_of_year(config: &Config, year: i32) -> impl Iterator<Item = (NaiveDate, &ProjectEntry)> {
+    let range = DateRange::year(year).unwrap_or_else(DateRange::all);
+    Query::for_range(range)
+        .entries(config)
+        .into_iter()
+        .map(|hit| (hit.record.date, hit.entry))
+}
+
+/// Sums up the hours per tag for a year.
+/// An entry with several tags counts towards every one of them, so the sum over all tags
+/// can be higher than the hours worked
+pub fn tag_totals(config: &Config, year: i32) -> BTreeMap<String, f64> {
+    let range = DateRange::year(year).unwrap_or_else(DateRange::all);
+    Query::for_range(range).grouped_by(GroupBy::Tag).totals(config)
+}
+
+/// Tags of an entry, or the untagged label
+fn tags_or_untagged(entry: &ProjectEntry) -> Vec<String> {
+    if entry.tags.is_empty() {
+        vec![String::from(UNTAGGED)]
+    } else {
+        entry.tags.clone()
+    }
+}
+
+/// Sums up the hours per project and tag for a year
+pub fn project_tag_totals(config: &Config, year: i32) -> BTreeMap<(String, String), f64> {
+    let mut totals = BTreeMap::new();
+    for (_, entry) in entries_of_year(config, year) {
+        for tag in tags_or_untagged(entry) {
+            *totals.entry((entry.project_name.code.clone(), tag)).or_insert(0.0) += entry.hours;
+        }
+    }
+    totals
+}
+
+/// Sums up the hours per tag for every month of a year
+/// Returns:
+///     month number -> tag -> hours
+pub fn tag_totals_by_month(config: &Config, year: i32) -> BTreeMap<u32, BTreeMap<String, f64>> {
+    let mut totals: BTreeMap<u32, BTreeMap<String, f64>> = BTreeMap::new();
+    for (date, entry) in entries_of_year(config, year) {
+        for tag in tags_or_untagged(entry) {
+            *totals.entry(date.month()).or_default().entry(tag).or_insert(0.0) += entry.hours;
+        }
+    }
+    totals
+}
+
+/// Summary of a month: totals per project and category, plus the details of every day
+#[derive(Debug, Clone)]
+pub struct MonthlyReport {
+    pub year: i32,
+    pub month: u32,
+    /// Customer projects, sorted by code
+    pub projects: Vec<ProjectHours>,
+    /// Internal categories, sorted by code
+    pub internal: Vec<ProjectHours>,
+    /// Records of the month, sorted by date
+    pub days: Vec<TimeRecord>,
+    /// Locked periods overlapping the month
+    pub locks: Vec<PeriodLock>
+}
+
+impl MonthlyReport {
+    /// Collects the report of a month
+    pub fn build(config: &Config, year: i32, month: u32) -> Result<MonthlyReport> {
+        let totals = filter_time_record_totals(config, year, month)?;
+        let (projects, internal) = split_internal_totals(config, totals);
+
+        let mut days: Vec<TimeRecord> = config.time_records
+            .iter()
+            .filter(|r| r.date.year() == year && r.date.month() == month)
+            .cloned()
+            .collect();
+        days.sort_by_key(|r| r.date);
+        let locks = DateRange::month(year, month)
+            .map(|range| config.locks.overlapping(range).into_iter().cloned().collect())
+            .unwrap_or_default();
+
+        Ok(MonthlyReport { year, month, projects, internal, days, locks })
+    }
+
+    /// Name of the month like "November 2025"
+    pub fn title(&self) -> String {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1)
+            .map(|d| d.format("%B %Y").to_string())
+            .unwrap_or_else(|| format!("{}/{}", self.month, self.year))
+    }
+
+    /// Hours allocated to projects and categories
+    pub fn total(&self) -> f64 {
+        self.projects.iter().chain(self.internal.iter()).map(|(_, h)| h).sum()
+    }
+
+    /// Net working hours of all days
+    pub fn net_total(&self) -> f64 {
+        self.days.iter().map(|d| d.get_net_hours()).sum()
+    }
+}
+
+/// Menu for the tag reports of a year
+pub fn tag_report(config: &Config) -> Result<()> {
+
+    let Some(year) = choose_year(config, "Please choose a year for the report")? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let options = vec!["Hours by Tag", "Hours by Project and Tag", "Tags over Time"];
+    let Some(ans) = Select::new("Which tag report?", options).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    println!("Tag report for {}", year);
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    match ans {
+        "Hours by Tag" => {
+            for (tag, hours) in tag_totals(config, year) {
+                println!("{:<20} {:>8.2}", tag, hours);
+            }
+        },
+        "Hours by Project and Tag" => {
+            let mut last_project = String::new();
+            for ((project, tag), hours) in project_tag_totals(config, year) {
+                if project != last_project {
+                    println!("Project: {}", project);
+                    last_project = project;
+                }
+                println!("    {:<20} {:>8.2}", tag, hours);
+            }
+        },
+        _ => {
+            for (month, totals) in tag_totals_by_month(config, year) {
+                let name = NaiveDate::from_ymd_opt(year, month, 1)
+                    .map(|d| d.format("%B").to_string())
+                    .unwrap_or_default();
+                println!("{}", name);
+                for (tag, hours) in totals {
+                    println!("    {:<20} {:>8.2}", tag, hours);
+                }
+            }
+        }
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    println!("Entries with several tags count towards each of them.\n");
+
+    Ok(())
+}
+
+/// Hours of one ISO week as a grid of days x projects
+#[derive(Debug, Clone)]
+pub struct WeekGrid {
+    pub year: i32,
+    pub week: u32,
+    /// Monday to Sunday
+    pub days: Vec<NaiveDate>,
+    /// Project codes with entries in this week, sorted
+    pub projects: Vec<String>,
+    pub hours: BTreeMap<(NaiveDate, String), f64>,
+    /// Net hours of the days with a TimeRecord
+    pub net_hours: BTreeMap<NaiveDate, f64>
+}
+
+impl WeekGrid {
+    /// Collects the grid for the ISO week containing `date`
+    pub fn build(config: &Config, date: NaiveDate) -> WeekGrid {
+        let iso = date.iso_week();
+        let monday = NaiveDate::from_isoywd_opt(iso.year(), iso.week(), Weekday::Mon)
+            .unwrap_or(date);
+        let days: Vec<NaiveDate> = monday.iter_days().take(7).collect();
+
+        let mut hours = BTreeMap::new();
+        let mut net_hours = BTreeMap::new();
+        for record in config.time_records.iter().filter(|r| days.contains(&r.date)) {
+            net_hours.insert(record.date, record.get_net_hours());
+            for entry in &record.project_entries {
+                *hours.entry((record.date, entry.project_name.code.clone())).or_insert(0.0) += entry.hours;
+            }
+        }
+
+        let mut projects: Vec<String> = hours.keys().map(|(_, code)| code.clone()).collect();
+        projects.sort();
+        projects.dedup();
+
+        WeekGrid { year: iso.year(), week: iso.week(), days, projects, hours, net_hours }
+    }
+
+    /// Hours of a project on a day
+    pub fn cell(&self, day: NaiveDate, code: &str) -> f64 {
+        self.hours.get(&(day, code.to_string())).copied().unwrap_or(0.0)
+    }
+
+    /// Hours allocated to projects on a day
+    pub fn day_total(&self, day: NaiveDate) -> f64 {
+        self.projects.iter().map(|p| self.cell(day, p)).sum()
+    }
+
+    /// Hours of a project over the whole week
+    pub fn project_total(&self, code: &str) -> f64 {
+        self.days.iter().map(|d| self.cell(*d, code)).sum()
+    }
+
+    /// Net hours of a day that are not allocated to any project
+    pub fn unallocated(&self, day: NaiveDate) -> f64 {
+        self.net_hours.get(&day).copied().unwrap_or(0.0) - self.day_total(day)
+    }
+
+    /// Net hours of the whole week
+    pub fn net_total(&self) -> f64 {
+        self.net_hours.values().sum()
+    }
+
+    /// Renders the grid as a text table
+    pub fn render(&self) -> String {
+        let fmt_hours = |h: f64| if h == 0.0 { String::from("-") } else { format!("{:.2}", h) };
+        let mut out = String::new();
+
+        out.push_str(&format!("Week {}/{} ({} - {})\n", self.week, self.year, self.days[0], self.days[6]));
+
+        let mut header = format!("{:<12}", "Day");
+        for project in &self.projects {
+            header.push_str(&format!("{:>10}", project));
+        }
+        header.push_str(&format!("{:>10}{:>10}{:>10}", "Total", "Net", "Unalloc."));
+        out.push_str(&header);
+        out.push('\n');
+        out.push_str(&"-".repeat(header.len()));
+        out.push('\n');
+
+        for day in &self.days {
+            let mut row = format!("{:<12}", day.format("%a %d.%m."));
+            for project in &self.projects {
+                row.push_str(&format!("{:>10}", fmt_hours(self.cell(*day, project))));
+            }
+            let (net, unallocated) = match self.net_hours.get(day) {
+                Some(net) => (fmt_hours(*net), fmt_hours(self.unallocated(*day))),
+                None => (String::from("-"), String::from("-")),
+            };
+            row.push_str(&format!("{:>10}{:>10}{:>10}", fmt_hours(self.day_total(*day)), net, unallocated));
+            out.push_str(&row);
+            out.push('\n');
+        }
+
+        out.push_str(&"-".repeat(header.len()));
+        out.push('\n');
+        let mut totals = format!("{:<12}", "Total");
+        for project in &self.projects {
+            totals.push_str(&format!("{:>10}", fmt_hours(self.project_total(project))));
+        }
+        let allocated: f64 = self.days.iter().map(|d| self.day_total(*d)).sum();
+        totals.push_str(&format!("{:>10}{:>10}{:>10}", fmt_hours(allocated), fmt_hours(self.net_total()), fmt_hours(self.net_total() - allocated)));
+        out.push_str(&totals);
+        out.push('\n');
+        out
+    }
+}
+
+/// Weekly timesheet. Starts with the current week, the user can move to previous and next weeks
+pub fn weekly_report(config: &Config) -> Result<()> {
+
+    let mut date = Local::now().date_naive();
+
+    loop {
+        println!("{}", WeekGrid::build(config, date).render());
+
+        let options = vec!["Previous Week", "Next Week", "Choose Week", "Back"];
+        match Select::new("Weekly report", options).prompt_skippable()? {
+            Some("Previous Week") => date = date - Days::new(7),
+            Some("Next Week") => date = date + Days::new(7),
+            Some("Choose Week") => {
+                if let Some(chosen) = DateSelect::new("Choose a day of the week:")
+                    .with_starting_date(date)
+                    .with_week_start(Weekday::Mon)
+                    .prompt_skippable()? {
+                    date = chosen;
+                }
+            },
+            _ => break Ok(()),
+        }
+    }
+}
+
+/// Totals of a single project over a date range
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectTotal {
+    pub code: String,
+    pub internal: bool,
+    pub hours: f64,
+    /// Number of project entries
+    pub entries: usize,
+    /// First and last day with an entry in the range
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    /// Share of all hours in the range, 0.0 - 1.0
+    pub share: f64
+}
+
+/// Sums up hours, entries and first/last activity per project in a date range.
+/// Sorted by hours, highest first
+pub fn project_totals(config: &Config, range: DateRange) -> Vec<ProjectTotal> {
+
+    let mut totals: BTreeMap<String, ProjectTotal> = BTreeMap::new();
+
+    for QueryHit { record, entry } in Query::for_range(range).entries(config) {
+        let code = &entry.project_name.code;
+        let total = totals.entry(code.clone()).or_insert_with(|| ProjectTotal {
+            code: code.clone(),
+            internal: config.is_internal(code),
+            hours: 0.0,
+            entries: 0,
+            first: record.date,
+            last: record.date,
+            share: 0.0
+        });
+        total.hours += entry.hours;
+        total.entries += 1;
+        total.first = total.first.min(record.date);
+        total.last = total.last.max(record.date);
+    }
+
+    let sum: f64 = totals.values().map(|t| t.hours).sum();
+    let mut totals: Vec<ProjectTotal> = totals.into_values().collect();
+    for total in totals.iter_mut() {
+        total.share = if sum > 0.0 { total.hours / sum } else { 0.0 };
+    }
+    totals.sort_by(|a, b| b.hours.total_cmp(&a.hours).then_with(|| a.code.cmp(&b.code)));
+    totals
+}
+
+/// First day with an entry for a project
+pub fn project_start(config: &Config, code: &str) -> Option<NaiveDate> {
+    config.time_records
+        .iter()
+        .filter(|r| r.project_entries.iter().any(|e| e.project_name.code == code))
+        .map(|r| r.date)
+        .min()
+}
+
+/// Asks the user for the date range of a report
+/// Returns:
+///     None if the user cancelled
+pub fn choose_date_range(config: &Config) -> Result<Option<DateRange>> {
+
+    let options = vec!["Custom Range", "Quarter", "Year", "Since Project Start", "All Time"];
+    let Some(ans) = Select::new("For what period?", options).prompt_skippable()? else {
+        return Ok(None);
+    };
+
+    let range = match ans {
+        "Custom Range" => {
+            let today = Local::now().date_naive();
+            let Some(from) = DateSelect::new("From:")
+                .with_starting_date(today)
+                .with_week_start(Weekday::Mon)
+                .prompt_skippable()? else {
+                return Ok(None);
+            };
+            let Some(to) = DateSelect::new("Until:")
+                .with_starting_date(today.max(from))
+                .with_min_date(from)
+                .with_week_start(Weekday::Mon)
+                .prompt_skippable()? else {
+                return Ok(None);
+            };
+            Some(DateRange::new(from, to))
+        },
+        "Quarter" => {
+            let Some(year) = choose_year(config, "Please choose a year")? else {
+                return Ok(None);
+            };
+            let Some(quarter) = Select::new("Which quarter?", vec![1, 2, 3, 4]).prompt_skippable()? else {
+                return Ok(None);
+            };
+            DateRange::quarter(year, quarter)
+        },
+        "Year" => {
+            let Some(year) = choose_year(config, "Please choose a year")? else {
+                return Ok(None);
+            };
+            DateRange::year(year)
+        },
+        "Since Project Start" => {
+            let Some(code) = choose_project(&config.project_records, "Since the start of which project?")? else {
+                return Ok(None);
+            };
+            match project_start(config, &code) {
+                Some(start) => Some(DateRange::new(start, NaiveDate::MAX)),
+                None => {
+                    println!("No entries for {} yet", code);
+                    return Ok(None);
+                }
+            }
+        },
+        _ => Some(DateRange::all()),
+    };
+
+    Ok(range)
+}
+
+/// Prints the project totals of a date range
+pub fn print_project_totals(totals: &[ProjectTotal], range: &DateRange) {
+
+    println!("Project totals ({})", range);
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    println!("{:<12}{:>10}{:>9}{:>12}{:>12}{:>8}", "Project", "Hours", "Entries", "First", "Last", "Share");
+    for t in totals {
+        let code = if t.internal { format!("{}*", t.code) } else { t.code.clone() };
+        println!("{:<12}{:>10.2}{:>9}{:>12}{:>12}{:>7.1}%", code, t.hours, t.entries, t.first, t.last, t.share * 100.0);
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    println!("Total: {:.2} hours. * internal (non-billable)\n", totals.iter().map(|t| t.hours).sum::<f64>());
+}
+
+/// Project totals over a date range chosen by the user
+pub fn project_totals_report(config: &Config) -> Result<()> {
+
+    let Some(range) = choose_date_range(config)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    print_project_totals(&project_totals(config, range), &range);
+    Ok(())
+}
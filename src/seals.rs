@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::locks::{PeriodLock, without_billing};
+
+/// File name of the seals over locked periods, next to the data files
+pub const SEALS_FILE: &str = "seals.json";
+
+/// Previous hash of the first seal in the chain
+pub const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 of some bytes as lowercase hex (FIPS 180-4)
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    h.iter().map(|v| format!("{:08x}", v)).collect()
+}
+
+/// Hash of the workdays of a date, serialized as JSON in the order of their ids.
+/// Billing the day after sealing keeps the hash, see without_billing
+pub fn day_hash(records: &[&TimeRecord]) -> Result<String> {
+    let mut records: Vec<TimeRecord> = records.iter().map(|r| without_billing(r)).collect();
+    records.sort_by_key(|r| r.id);
+    let json = serde_json::to_string(&records).context("Failed to serialize the workdays for sealing")?;
+    Ok(sha256_hex(json.as_bytes()))
+}
+
+/// Hashes of all dates with workdays in a lock's period
+fn day_hashes(from: NaiveDate, to: NaiveDate, records: &[TimeRecord]) -> Result<Vec<DaySeal>> {
+    let mut dates: Vec<NaiveDate> = records.iter().map(|r| r.date).filter(|d| from <= *d && *d <= to).collect();
+    dates.sort();
+    dates.dedup();
+    dates.into_iter().map(|date| {
+        let day: Vec<&TimeRecord> = records.iter().filter(|r| r.date == date).collect();
+        Ok(DaySeal { date, hash: day_hash(&day)? })
+    }).collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DaySeal {
+    pub date: NaiveDate,
+    pub hash: String
+}
+
+/// Hashes over the workdays of a locked period, chained to the previous seal
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Seal {
+    pub period: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Same as the locked_at of the sealed lock
+    pub sealed_at: NaiveDateTime,
+    pub days: Vec<DaySeal>,
+    /// Hash of the previous seal, GENESIS for the first one
+    pub prev: String,
+    pub hash: String
+}
+
+impl Seal {
+    /// Hash over the previous hash and everything the seal covers
+    pub fn compute_hash(&self) -> String {
+        let mut contents = format!("{}\n{}\n{}\n{}\n{}\n", self.prev, self.period, self.from, self.to, self.sealed_at);
+        for day in &self.days {
+            contents.push_str(&format!("{} {}\n", day.date, day.hash));
+        }
+        sha256_hex(contents.as_bytes())
+    }
+}
+
+/// Chain of seals, one per lock of a period
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Seals {
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub seals: Vec<Seal>
+}
+
+impl Seals {
+    /// Loads the seals next to the given data file. A missing file has none
+    pub fn open(data_path: &Path) -> Result<Seals> {
+        let path = data_path.with_file_name(SEALS_FILE);
+        let mut seals: Seals = match fs::read_to_string(&path) {
+            Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to deserialze seals for path: {:#?}", path))?,
+            _ => Seals::default(),
+        };
+        seals.path = path;
+        Ok(seals)
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize seals")?;
+        fs::write(&self.path, json).with_context(|| format!("Failed to write seals to {:#?}", self.path))
+    }
+
+    /// Appends a seal over the workdays of a lock. Does not write, call Seals.write after usage
+    pub fn seal(&mut self, lock: &PeriodLock, records: &[TimeRecord]) -> Result<&Seal> {
+        let prev = self.seals.last().map_or(GENESIS.to_string(), |s| s.hash.clone());
+        let mut seal = Seal {
+            period: lock.period.clone(),
+            from: lock.from,
+            to: lock.to,
+            sealed_at: lock.locked_at,
+            days: day_hashes(lock.from, lock.to, records)?,
+            prev,
+            hash: String::new(),
+        };
+        seal.hash = seal.compute_hash();
+        self.seals.push(seal);
+        Ok(self.seals.last().expect("seal was added"))
+    }
+}
+
+/// Something that doesn't match its seal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealProblem {
+    /// The seal itself was edited, or a seal before it removed
+    BrokenChain { period: String },
+    DayChanged { period: String, date: NaiveDate },
+    DayAdded { period: String, date: NaiveDate },
+    DayRemoved { period: String, date: NaiveDate },
+}
+
+impl std::fmt::Display for SealProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SealProblem::BrokenChain { period } => write!(f, "{}: the seal doesn't match the chain", period),
+            SealProblem::DayChanged { period, date } => write!(f, "{}: {} was changed after sealing", period, date),
+            SealProblem::DayAdded { period, date } => write!(f, "{}: {} was added after sealing", period, date),
+            SealProblem::DayRemoved { period, date } => write!(f, "{}: {} was removed after sealing", period, date),
+        }
+    }
+}
+
+/// Recomputes the chain of seals and compares the seals of periods that are still locked with the workdays.
+/// Seals of periods unlocked since are only checked as part of the chain
+/// Returns:
+///     Every problem found, none if the chain and the locked workdays are unchanged
+pub fn verify_seals(config: &Config) -> Result<Vec<SealProblem>> {
+    let mut problems = Vec::new();
+    let mut prev = GENESIS;
+    for seal in &config.seals.seals {
+        if seal.prev != prev || seal.compute_hash() != seal.hash {
+            problems.push(SealProblem::BrokenChain { period: seal.period.clone() });
+        }
+        prev = &seal.hash;
+
+        let still_locked = config.locks.locks.iter()
+            .any(|l| l.period == seal.period && l.locked_at == seal.sealed_at);
+        if !still_locked {
+            continue;
+        }
+        let period = || seal.period.clone();
+        let days = day_hashes(seal.from, seal.to, &config.time_records)?;
+        for day in &seal.days {
+            match days.iter().find(|d| d.date == day.date) {
+                None => problems.push(SealProblem::DayRemoved { period: period(), date: day.date }),
+                Some(now) if now.hash != day.hash => problems.push(SealProblem::DayChanged { period: period(), date: day.date }),
+                Some(_) => {},
+            }
+        }
+        for day in days.iter().filter(|d| !seal.days.iter().any(|s| s.date == d.date)) {
+            problems.push(SealProblem::DayAdded { period: period(), date: day.date });
+        }
+    }
+    Ok(problems)
+}
+
+/// Prints the result of verify_seals
+/// Returns:
+///     Error if anything doesn't match its seal
+pub fn print_verification(config: &Config) -> Result<()> {
+    let problems = verify_seals(config)?;
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    for seal in &config.seals.seals {
+        println!("{} sealed {} ({} days) {}", seal.period, seal.sealed_at.format("%Y-%m-%d %H:%M"), seal.days.len(), &seal.hash[..16]);
+    }
+    if problems.is_empty() {
+        println!("Verified {} seals, nothing was changed after sealing", config.seals.seals.len());
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    if !problems.is_empty() {
+        bail!("{} problems found", problems.len());
+    }
+    Ok(())
+}
+
+impl Config {
+    /// Locks a period and seals its workdays. Writes the locks and the seals
+    pub fn lock_period(&mut self, period: &str) -> Result<PeriodLock> {
+        let lock = self.locks.lock(period)?.clone();
+        self.seals.seal(&lock, &self.time_records)?;
+        self.locks.write()?;
+        self.seals.write()?;
+        self.git_commit(&format!("Lock {}", lock.period))?;
+        Ok(lock)
+    }
+}
@@ -0,0 +1,199 @@
+use std::str::FromStr;
+
+use inquire::Select;
+use regex::Regex;
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::query::{Query, QueryHit};
+use crate::utils::edit_project_entry;
+
+/// Full-text search over the activities of the project entries.
+///
+/// Terms are separated by spaces and all of them must match (case-insensitive):
+/// - `word` matches a whole word
+/// - `"some phrase"` matches the words in this order
+/// - `/regex/` matches a regular expression
+#[derive(Debug, Clone)]
+pub struct Search {
+    pub patterns: Vec<Regex>
+}
+
+/// Splits the input into terms, keeping quoted phrases and /regexes/ together
+fn split_terms(input: &str) -> Result<Vec<String>> {
+    let mut terms = Vec::new();
+    let mut chars = input.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut term = String::from(c);
+        if c == '"' || c == '/' {
+            // Read until the closing delimiter, which may be escaped in a regex
+            let mut closed = false;
+            while let Some(next) = chars.next() {
+                term.push(next);
+                if next == '\\' && c == '/' {
+                    if let Some(escaped) = chars.next() {
+                        term.push(escaped);
+                    }
+                } else if next == c {
+                    closed = true;
+                    break;
+                }
+            }
+            if !closed {
+                bail!("Missing closing {} in {}", c, term);
+            }
+        } else {
+            while let Some(next) = chars.next_if(|n| !n.is_whitespace()) {
+                term.push(next);
+            }
+        }
+        terms.push(term);
+    }
+    Ok(terms)
+}
+
+/// Compiles a single term into a case-insensitive pattern
+fn term_pattern(term: &str) -> Result<Regex> {
+    let pattern = if let Some(regex) = term.strip_prefix('/').and_then(|t| t.strip_suffix('/')) {
+        format!("(?i){}", regex)
+    } else if let Some(phrase) = term.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        let words: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+        format!("(?i){}", words.join(r"\s+"))
+    } else {
+        let starts_word = term.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let ends_word = term.chars().last().is_some_and(|c| c.is_alphanumeric() || c == '_');
+        format!(
+            "(?i){}{}{}",
+            if starts_word { r"\b" } else { "" },
+            regex::escape(term),
+            if ends_word { r"\b" } else { "" }
+        )
+    };
+    Regex::new(&pattern).with_context(|| format!("Invalid search term {}", term))
+}
+
+impl FromStr for Search {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Search> {
+        Search::from_terms(&split_terms(s)?)
+    }
+}
+
+impl Search {
+    fn from_terms(terms: &[String]) -> Result<Search> {
+        let patterns = terms
+            .iter()
+            .filter(|t| !matches!(t.as_str(), "\"\"" | "//"))
+            .map(|t| term_pattern(t))
+            .collect::<Result<Vec<Regex>>>()?;
+
+        if patterns.is_empty() {
+            bail!("Please enter at least one search term");
+        }
+        Ok(Search { patterns })
+    }
+
+    /// Search from command line arguments, one term each. The shell removed the quotes already,
+    /// so an argument with spaces is a phrase
+    pub fn from_args(args: &[String]) -> Result<Search> {
+        let terms: Vec<String> = args
+            .iter()
+            .map(|arg| arg.trim())
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| {
+                let delimited = |d: char| arg.len() > 1 && arg.starts_with(d) && arg.ends_with(d);
+                if delimited('"') || delimited('/') || !arg.contains(char::is_whitespace) {
+                    arg.to_string()
+                } else {
+                    format!("\"{}\"", arg)
+                }
+            })
+            .collect();
+        Search::from_terms(&terms)
+    }
+
+    /// Checks if all terms match the text
+    pub fn matches(&self, text: &str) -> bool {
+        self.patterns.iter().all(|p| p.is_match(text))
+    }
+
+    /// All entries of the query whose activity matches, sorted by date
+    pub fn hits<'a>(&self, config: &'a Config, query: &Query) -> Vec<QueryHit<'a>> {
+        query.entries(config)
+            .into_iter()
+            .filter(|hit| self.matches(&hit.entry.activity))
+            .collect()
+    }
+}
+
+/// One line per hit: date, project, hours and activity
+pub fn hit_label(hit: &QueryHit) -> String {
+    format!("{} | {}", hit.record.date, hit.entry.label())
+}
+
+/// Prints the search hits
+pub fn print_hits(hits: &[QueryHit]) {
+    if hits.is_empty() {
+        println!("No matching entries");
+        return;
+    }
+    for hit in hits {
+        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+        println!("Date: {}  Entry: #{}", hit.record.date, hit.entry.id);
+        println!("Project: {}  Hours: {}", hit.entry.project_name.code, hit.entry.hours);
+        println!("Activity: {}", hit.entry.activity);
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    println!("{} entries found\n", hits.len());
+}
+
+/// Lets the user choose one of the hits and edit it
+/// Returns:
+///     The id of the chosen entry, None if cancelled
+pub fn choose_hit(hits: &[QueryHit]) -> Result<Option<u64>> {
+    if hits.is_empty() {
+        return Ok(None);
+    }
+    let labels: Vec<String> = hits.iter().map(hit_label).collect();
+    let Some(selected) = Select::new("Which entry would you like to edit? (Esc to return)", labels.clone())
+        .prompt_skippable()? else {
+        return Ok(None);
+    };
+    let index = labels.iter().position(|l| *l == selected).context("Selected entry not found")?;
+    Ok(Some(hits[index].entry.id))
+}
+
+/// Search, print the hits and optionally edit one of them
+pub fn search_and_edit(config: &mut Config, search: &Search, query: &Query, edit: bool) -> Result<()> {
+    let hits = search.hits(config, query);
+    print_hits(&hits);
+
+    if edit && let Some(entry_id) = choose_hit(&hits)? {
+        edit_project_entry(config, entry_id)?;
+    }
+    Ok(())
+}
+
+/// Menu entry for the full-text search
+pub fn search_menu(config: &mut Config) -> Result<()> {
+
+    let search = loop {
+        let Some(input) = inquire::Text::new("Search for:")
+            .with_help_message("Words, \"a phrase\" or /regex/. All terms must match")
+            .prompt_skippable()? else {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(());
+        };
+        match input.parse::<Search>() {
+            Ok(search) => break search,
+            Err(e) => println!("{}", e),
+        }
+    };
+
+    search_and_edit(config, &search, &Query::default(), true)
+}
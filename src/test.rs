@@ -1,22 +1,50 @@
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{models::*, utils::get_activity_hours};
+    use crate::{models::*, utils::{get_activity_hours, find_project, split_internal_totals}};
+    use crate::reports::*;
+    use crate::query::*;
+    use crate::search::*;
+    use crate::export::*;
+    use crate::render::*;
+    use crate::pdf::*;
+    use crate::timesheet::timesheet_pdf;
+    use crate::xlsx::range_xlsx;
+    use crate::invoice::*;
+    use crate::import::*;
+    use crate::trackers::*;
+    use crate::ics::*;
+    use crate::timeclock::*;
+    use crate::org::*;
+    use crate::journal::*;
+    use crate::undo::*;
+    use crate::history::*;
+    use crate::locks::*;
+    use crate::seals::*;
+    use crate::instance::*;
+    use crate::git::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
 
     fn test_time_record() -> TimeRecord {
         let t_for_test = TimeRecord {
+            id: 1,
             date: chrono::NaiveDate::from_ymd_opt(2025, 11, 9).unwrap(),
             start_time: chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
             end_time: chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap(), // 10 H 
             pause_minutes: 0.5, // 9.5 H
             project_entries: vec![
                 ProjectEntry {
-                    project_name: Project { code: String::from("INEK"), allocation: 1.0 },
+                    id: 1,
+                    project_name: Project { code: String::from("INEK"), allocation: 1.0, internal: false, rate: None, client: None },
                     hours: 3.5,
-                    activity: String::from("I ran a test")
-
+                    activity: String::from("I ran a test"),
+                    tags: vec![String::from("testing")],
+                    invoice: None,
+                    history: vec![]
                 }
             ],
+            history: vec![],
         };
         return t_for_test;
     }
@@ -41,4 +69,1302 @@ mod tests {
         let remaining = t_for_test.remaining_hours();
         assert_eq!(6.0, remaining)
     }
-}
\ No newline at end of file
+
+    fn test_config() -> Config {
+        let mut config = Config {
+            time_records: vec![test_time_record()],
+            project_records: vec![Project { code: String::from("INEK"), allocation: 1.0, internal: false, rate: None, client: None }],
+            settings: Settings::default(),
+            t_path: PathBuf::from("data.json"),
+            p_path: PathBuf::from("projects.json"),
+            s_path: PathBuf::from("settings.json"),
+            journal: Journal::default(),
+            undo: UndoStack::default(),
+            reason: None,
+            locks: Locks::default(),
+            seals: Seals::default(),
+            tombstones: Tombstones::default(),
+            on_disk: None,
+        };
+        config.seed_categories();
+        config
+    }
+
+    #[test]
+    fn test_seed_categories() {
+        let mut config = test_config();
+        assert!(config.is_internal("INT"));
+        assert!(config.is_internal("Vacation"));
+        assert!(!config.is_internal("INEK"));
+
+        // A deleted default stays deleted while other categories are left and no entry uses it
+        config.project_records.retain(|p| p.code != "Vacation");
+        assert!(!config.seed_categories());
+        assert_eq!(config.categories().count(), 1);
+
+        // Entries using a default bring it back, e.g. INT of catalogs that never stored it
+        config.project_records.retain(|p| p.code != "INT");
+        config.project_records.push(Project::category("Admin"));
+        config.time_records[0].project_entries[0].project_name.code = String::from("INT");
+        assert!(config.seed_categories());
+        assert!(config.is_internal("INT"));
+        assert!(!config.project_records.iter().any(|p| p.code == "Vacation"));
+        config.time_records[0].project_entries[0].project_name.code = String::from("INEK");
+
+        // Without any category the defaults are seeded, a legacy INT project becomes the category
+        config.project_records.retain(|p| !p.internal);
+        config.project_records.push(Project { code: String::from("INT"), allocation: 1.0, internal: false, rate: None, client: None });
+        assert!(config.seed_categories());
+        assert!(config.is_internal("Vacation"));
+        assert!(config.is_internal("INT"));
+        assert_eq!(config.project_records.iter().filter(|p| p.code == "INT").count(), 1);
+        assert!(!config.is_internal("INEK"));
+    }
+
+    #[test]
+    fn test_find_project_uses_catalog() {
+        let config = test_config();
+        assert!(find_project(&config.project_records, "INT").unwrap().internal);
+        assert!(find_project(&config.project_records, "ADMIN").is_none());
+    }
+
+    #[test]
+    fn test_split_internal_totals() {
+        let config = test_config();
+        let totals = HashMap::from([
+            (String::from("INEK"), 3.5),
+            (String::from("INT"), 1.0),
+            (String::from("Vacation"), 8.0),
+        ]);
+        let (projects, internal) = split_internal_totals(&config, totals);
+        assert_eq!(projects, vec![(String::from("INEK"), 3.5)]);
+        assert_eq!(internal, vec![(String::from("INT"), 1.0), (String::from("Vacation"), 8.0)]);
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        let tags = ProjectEntry::extract_tags("#Meeting with the team, then #review and #meeting again. # nothing");
+        assert_eq!(tags, vec![String::from("meeting"), String::from("review")]);
+    }
+
+    #[test]
+    fn test_tag_reports() {
+        let mut config = test_config();
+        config.time_records[0].project_entries.push(ProjectEntry {
+            id: 2,
+            project_name: Project::category("INT"),
+            hours: 2.0,
+            activity: String::from("Weekly #meeting about #testing"),
+            tags: vec![String::from("meeting"), String::from("testing")],
+            invoice: None,
+            history: vec![]
+        });
+        config.time_records[0].project_entries.push(ProjectEntry {
+            id: 3,
+            project_name: Project::category("INT"),
+            hours: 1.0,
+            activity: String::from("Mails"),
+            tags: vec![],
+            invoice: None,
+            history: vec![]
+        });
+
+        let totals = tag_totals(&config, 2025);
+        assert_eq!(totals["testing"], 5.5);
+        assert_eq!(totals["meeting"], 2.0);
+        assert_eq!(totals[UNTAGGED], 1.0);
+
+        let by_project = project_tag_totals(&config, 2025);
+        assert_eq!(by_project[&(String::from("INEK"), String::from("testing"))], 3.5);
+        assert_eq!(by_project[&(String::from("INT"), String::from("testing"))], 2.0);
+
+        let by_month = tag_totals_by_month(&config, 2025);
+        assert_eq!(by_month[&11]["testing"], 5.5);
+        assert!(tag_totals(&config, 2024).is_empty());
+    }
+
+    #[test]
+    fn test_assign_missing_ids() {
+        let mut config = test_config();
+        let mut legacy = test_time_record();
+        legacy.id = 0;
+        legacy.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        legacy.project_entries[0].id = 0;
+        legacy.project_entries.push(legacy.project_entries[0].clone());
+        config.time_records.push(legacy);
+
+        config.assign_missing_ids();
+
+        assert_eq!(config.time_records[1].id, 2);
+        let ids: Vec<u64> = config.time_records[1].project_entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(config.find_entry(3).unwrap().0.id, 2);
+        assert_eq!(config.next_entry_id(), 4);
+    }
+
+    #[test]
+    fn test_merge_entries() {
+        let mut entry = test_time_record().project_entries[0].clone();
+        let mut other = entry.clone();
+        other.id = 2;
+        other.hours = 1.0;
+        other.activity = String::from("#review of the results");
+        other.tags = vec![String::from("review"), String::from("testing")];
+
+        entry.merge(other);
+
+        assert_eq!(entry.id, 1);
+        assert_eq!(entry.hours, 4.5);
+        assert_eq!(entry.activity, "I ran a test; #review of the results");
+        assert_eq!(entry.tags, vec![String::from("testing"), String::from("review")]);
+    }
+
+    #[test]
+    fn test_reject_duplicate_entry() {
+        let mut config = test_config();
+        config.settings.duplicate_policy = DuplicatePolicy::Reject;
+        let duplicate = test_time_record().project_entries[0].clone();
+
+        let result = config.add_project_entry(config.time_records[0].date, duplicate);
+
+        assert!(result.is_err());
+        assert_eq!(config.time_records[0].project_entries.len(), 1);
+    }
+
+    #[test]
+    fn test_week_grid() {
+        let mut config = test_config();
+        // Previous sunday belongs to ISO week 45, must not show up
+        let mut sunday = test_time_record();
+        sunday.id = 2;
+        sunday.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 9).unwrap();
+        config.time_records[0].date = chrono::NaiveDate::from_ymd_opt(2025, 11, 12).unwrap();
+        config.time_records.push(sunday);
+
+        let grid = WeekGrid::build(&config, chrono::NaiveDate::from_ymd_opt(2025, 11, 16).unwrap());
+        let wednesday = chrono::NaiveDate::from_ymd_opt(2025, 11, 12).unwrap();
+
+        assert_eq!(grid.week, 46);
+        assert_eq!(grid.days[0], chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap());
+        assert_eq!(grid.projects, vec![String::from("INEK")]);
+        assert_eq!(grid.cell(wednesday, "INEK"), 3.5);
+        assert_eq!(grid.unallocated(wednesday), 6.0);
+        assert_eq!(grid.project_total("INEK"), 3.5);
+        assert_eq!(grid.net_total(), 9.5);
+        assert!(grid.render().contains("Week 46/2025"));
+    }
+
+    #[test]
+    fn test_date_ranges() {
+        let q4 = DateRange::quarter(2025, 4).unwrap();
+        assert_eq!(q4.from, chrono::NaiveDate::from_ymd_opt(2025, 10, 1).unwrap());
+        assert_eq!(q4.to, chrono::NaiveDate::from_ymd_opt(2025, 12, 31).unwrap());
+        assert_eq!(DateRange::month(2024, 2).unwrap().to, chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert!(DateRange::quarter(2025, 5).is_none());
+        assert!(DateRange::all().contains(chrono::NaiveDate::from_ymd_opt(1990, 1, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_project_totals() {
+        let mut config = test_config();
+        let mut later = test_time_record();
+        later.id = 2;
+        later.date = chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+        later.project_entries[0].hours = 2.5;
+        later.project_entries.push(ProjectEntry {
+            id: 3,
+            project_name: Project::category("INT"),
+            hours: 4.0,
+            activity: String::new(),
+            tags: vec![],
+            invoice: None,
+            history: vec![]
+        });
+        config.time_records.push(later);
+
+        let totals = project_totals(&config, DateRange::all());
+        assert_eq!(totals[0].code, "INEK");
+        assert_eq!(totals[0].hours, 6.0);
+        assert_eq!(totals[0].entries, 2);
+        assert_eq!(totals[0].first, chrono::NaiveDate::from_ymd_opt(2025, 11, 9).unwrap());
+        assert_eq!(totals[0].last, chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap());
+        assert_eq!(totals[0].share, 0.6);
+        assert!(totals[1].internal);
+
+        let november = project_totals(&config, DateRange::month(2025, 11).unwrap());
+        assert_eq!(november.len(), 1);
+        assert_eq!(november[0].share, 1.0);
+        assert_eq!(project_start(&config, "INT"), Some(chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query: Query = "project:NLS,INEK tag:#Meeting since:2025-10-01 until:2025-12-31 group:week".parse().unwrap();
+        assert_eq!(query.projects, vec![String::from("NLS"), String::from("INEK")]);
+        assert_eq!(query.tags, vec![String::from("meeting")]);
+        assert_eq!(query.range, DateRange::quarter(2025, 4).unwrap());
+        assert_eq!(query.group, Some(GroupBy::Week));
+
+        assert!("project".parse::<Query>().is_err());
+        assert!("colour:red".parse::<Query>().is_err());
+        assert!("since:2025-12-01 until:2025-01-01".parse::<Query>().is_err());
+        assert_eq!("month:2025-11".parse::<Query>().unwrap().range, DateRange::month(2025, 11).unwrap());
+    }
+
+    #[test]
+    fn test_query_totals() {
+        let mut config = test_config();
+        config.time_records[0].project_entries.push(ProjectEntry {
+            id: 2,
+            project_name: Project::category("INT"),
+            hours: 2.0,
+            activity: String::from("#meeting"),
+            tags: vec![String::from("meeting")],
+            invoice: None,
+            history: vec![]
+        });
+
+        let by_project: Query = "year:2025 group:project".parse().unwrap();
+        let totals = by_project.totals(&config);
+        assert_eq!(totals["INEK"], 3.5);
+        assert_eq!(totals["INT"], 2.0);
+
+        let meetings: Query = "tag:meeting group:week".parse().unwrap();
+        assert_eq!(meetings.totals(&config)["2025-W45"], 2.0);
+
+        let billable: Query = "is:billable".parse().unwrap();
+        assert_eq!(billable.entries(&config).len(), 1);
+        assert_eq!(billable.totals(&config)["Total"], 3.5);
+
+        assert!("project:NLS".parse::<Query>().unwrap().entries(&config).is_empty());
+    }
+
+    #[test]
+    fn test_search_terms() {
+        let search: Search = r#"test "ran a" /I\s+r[au]n/"#.parse().unwrap();
+        assert_eq!(search.patterns.len(), 3);
+        assert!(search.matches("I ran a TEST"));
+        assert!(!search.matches("I ran a testcase"));
+        assert!(!search.matches("I ran   the test"));
+
+        assert!("\"open phrase".parse::<Search>().is_err());
+        assert!("/[unclosed/".parse::<Search>().is_err());
+        assert!("   ".parse::<Search>().is_err());
+
+        // Arguments keep the shell's quoting: one term each, with spaces a phrase
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let search = Search::from_args(&args(&["code review", "test"])).unwrap();
+        assert_eq!(search.patterns.len(), 2);
+        assert!(search.matches("Code  review of the test"));
+        assert!(!search.matches("review the code test"));
+        assert!(Search::from_args(&args(&["/I\\s+ran/", "\"ran a\""])).unwrap().matches("I ran a test"));
+        assert!(Search::from_args(&args(&[" "])).is_err());
+    }
+
+    #[test]
+    fn test_search_hits() {
+        let config = test_config();
+        let search: Search = "test".parse().unwrap();
+        let hits = search.hits(&config, &Query::default());
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.id, 1);
+
+        let other_project: Query = "project:NLS".parse().unwrap();
+        assert!(search.hits(&config, &other_project).is_empty());
+    }
+
+    #[test]
+    fn test_entries_csv() {
+        let mut config = test_config();
+        let mut empty_day = test_time_record();
+        empty_day.id = 2;
+        empty_day.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        empty_day.project_entries.clear();
+        config.time_records.push(empty_day);
+        config.time_records[0].project_entries[0].activity = String::from("Tests; more tests");
+
+        let german = CsvFormat { delimiter: ';', decimal_separator: ',' };
+        let csv = entries_csv(&config, DateRange::all(), &german).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "date;start;end;pause;net_hours;entry_id;project;hours;activity;tags");
+        assert_eq!(lines[1], "2025-11-09;08:00;18:00;0,50;9,50;1;INEK;3,50;\"Tests; more tests\";testing");
+        assert_eq!(lines[2], "2025-11-10;08:00;18:00;0,50;9,50;;;;;");
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_report_csv() {
+        let config = test_config();
+        let format = CsvFormat::default();
+        let monthly = report_csv(&config, CsvReport::Monthly, DateRange::year(2025).unwrap(), &format).unwrap();
+        assert_eq!(monthly, "month,project,internal,hours\n2025-11,INEK,no,3.50\n");
+
+        let totals = report_csv(&config, CsvReport::Totals, DateRange::all(), &format).unwrap();
+        assert!(totals.ends_with("INEK,no,3.50,1,2025-11-09,2025-11-09,100.00\n"));
+
+        let invalid = CsvFormat { delimiter: '§', decimal_separator: ',' };
+        assert!(entries_csv(&config, DateRange::all(), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_render_monthly() {
+        let mut config = test_config();
+        config.time_records[0].project_entries[0].activity = String::from("<script> | pipes");
+        let report = MonthlyReport::build(&config, 2025, 11).unwrap();
+        assert_eq!(report.title(), "November 2025");
+        assert_eq!(report.total(), 3.5);
+        assert_eq!(report.net_total(), 9.5);
+
+        let text = render_monthly(&report, OutputFormat::Text);
+        assert!(text.starts_with("Hour for 11/2025\n=== Projects ===\n"));
+
+        let markdown = render_monthly(&report, OutputFormat::Markdown);
+        assert!(markdown.contains("| INEK | Projects | 3.50 |"));
+        assert!(markdown.contains("| Sun 2025-11-09 | 08:00 | 18:00 | 0.50 | 9.50 | 3.50 |"));
+        assert!(markdown.contains("- **INEK** (3.50 h): <script> \\| pipes"));
+
+        let html = render_monthly(&report, OutputFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;script&gt; | pipes"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let lines = wrap_text("I put some data into salesforce and tested the import", 100.0, 10.0, Font::Regular);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| text_width(l, 10.0, Font::Regular) <= 100.0));
+        assert_eq!(lines.join(" "), "I put some data into salesforce and tested the import");
+        assert_eq!(wrap_text("", 100.0, 10.0, Font::Regular), vec![String::new()]);
+    }
+
+    /// Checks that no text of a PDF starts below the bottom margin
+    fn assert_text_within_margins(pdf: &str) {
+        for op in pdf.split("BT ").skip(1) {
+            let td = op.split(" Td").next().unwrap();
+            let y: f64 = td.rsplit(' ').next().unwrap().parse().unwrap();
+            assert!(y >= crate::pdf::PdfDocument::MARGIN, "text at y {}", y);
+        }
+    }
+
+    #[test]
+    fn test_timesheet_pdf() {
+        let mut config = test_config();
+        for day in 10..=30 {
+            let mut record = test_time_record();
+            record.id = day as u64;
+            record.date = chrono::NaiveDate::from_ymd_opt(2025, 11, day).unwrap();
+            record.project_entries[0].activity = "Long (activity) text ".repeat(10);
+            config.time_records.push(record);
+        }
+        let report = MonthlyReport::build(&config, 2025, 11).unwrap();
+        let header = TimesheetHeader { name: String::from("Jane Doe"), employee_number: String::from("42"), customer: String::from("ACME") };
+
+        let pdf = timesheet_pdf(&report, &header, false);
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(Timesheet November 2025)"));
+        assert!(text.contains("(Date, signature ACME)"));
+        assert!(text.contains("Long \\(activity\\) text"));
+        assert!(text.contains("/Count 2 "));
+        assert_text_within_margins(&text);
+
+        // The xref table points to the objects
+        let startxref: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref"));
+        let xref = String::from_utf8_lossy(&pdf[startxref..]).to_string();
+        let first_offset: usize = xref.lines().nth(3).unwrap()[..10].parse().unwrap();
+        assert!(pdf[first_offset..].starts_with(b"1 0 obj"));
+
+        // A day longer than a page continues on the next one instead of running over the margin
+        let mut config = test_config();
+        let record = &mut config.time_records[0];
+        record.project_entries = (1..=80).map(|id| ProjectEntry { id, hours: 0.1, ..record.project_entries[0].clone() }).collect();
+        let report = MonthlyReport::build(&config, 2025, 11).unwrap();
+        let text = String::from_utf8_lossy(&timesheet_pdf(&report, &header, false)).to_string();
+        assert!(text.contains("/Count 2 "));
+        assert_eq!(text.matches("(Sun 09.11.2025)").count(), 1);
+        assert_eq!(text.matches("(Project entries)").count(), 2);
+        assert_text_within_margins(&text);
+    }
+
+    /// Reads a part of an XLSX workbook
+    fn xlsx_part(xlsx: &[u8], name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(xlsx)).unwrap();
+        let mut part = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut part).unwrap();
+        part
+    }
+
+    /// Style index, type and value of a cell in a sheet's XML
+    fn xlsx_cell(sheet: &str, cell: &str) -> (Option<String>, Option<String>, String) {
+        let re = regex::Regex::new(&format!(r#"<c r="{}"([^>]*)>(?:<f>[^<]*</f>)?<v>([^<]*)</v></c>"#, cell)).unwrap();
+        let caps = re.captures(sheet).unwrap_or_else(|| panic!("no cell {}", cell));
+        let attr = |name: &str| regex::Regex::new(&format!(r#"{}="([^"]*)""#, name)).unwrap()
+            .captures(&caps[1]).map(|c| c[1].to_string());
+        (attr(" s"), attr(" t"), caps[2].to_string())
+    }
+
+    #[test]
+    fn test_range_xlsx() {
+        let mut config = test_config();
+        config.time_records.push(test_time_record());
+        let mut other_day = test_time_record();
+        other_day.id = 3;
+        other_day.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        other_day.project_entries[0].hours = 2.0;
+        let mut other_project = other_day.project_entries[0].clone();
+        other_project.id = 2;
+        other_project.project_name.code = String::from("OTHER");
+        other_project.hours = 1.25;
+        other_day.project_entries.push(other_project);
+        config.time_records.push(other_day);
+
+        let xlsx = range_xlsx(&config, DateRange::month(2025, 11).unwrap()).unwrap();
+        assert!(xlsx.starts_with(b"PK"));
+
+        // Dates are serial numbers with a date format, hours are numbers
+        let styles = xlsx_part(&xlsx, "xl/styles.xml");
+        let formats: Vec<&str> = styles.split("<cellXfs").nth(1).unwrap().split("<xf ").skip(1).collect();
+        let format_code = |style: &Option<String>| {
+            let xf = formats[style.as_ref().unwrap().parse::<usize>().unwrap()];
+            let id = xf.split("numFmtId=\"").nth(1).unwrap().split('"').next().unwrap();
+            styles.split(&format!("numFmtId=\"{}\" formatCode=\"", id)).nth(1).unwrap().split('"').next().unwrap().to_string()
+        };
+        let serial = (chrono::NaiveDate::from_ymd_opt(2025, 11, 9).unwrap() - chrono::NaiveDate::from_ymd_opt(1899, 12, 30).unwrap()).num_days();
+
+        let workdays = xlsx_part(&xlsx, "xl/worksheets/sheet1.xml");
+        let (style, kind, value) = xlsx_cell(&workdays, "A2");
+        assert_eq!((kind, value), (None, serial.to_string()));
+        assert_eq!(format_code(&style), "yyyy-mm-dd");
+        let (style, kind, value) = xlsx_cell(&workdays, "B2");
+        assert_eq!((kind, value.parse::<f64>().unwrap()), (None, 8.0 / 24.0));
+        assert_eq!(format_code(&style), "hh:mm");
+        let (style, kind, value) = xlsx_cell(&workdays, "E2");
+        assert_eq!((kind, value), (None, String::from("9.5")));
+        assert_eq!(format_code(&style), "0.00");
+        assert_eq!(xlsx_cell(&workdays, "E5").2, "28.5");
+        assert_eq!(xlsx_cell(&workdays, "F5").2, "10.25");
+        assert!(workdays.contains("<f>SUM(E2:E4)</f>"));
+
+        let entries = xlsx_part(&xlsx, "xl/worksheets/sheet2.xml");
+        assert_eq!(format_code(&xlsx_cell(&entries, "A5").0), "yyyy-mm-dd");
+        let (style, kind, value) = xlsx_cell(&entries, "E5");
+        assert_eq!((kind, value), (None, String::from("1.25")));
+        assert_eq!(format_code(&style), "0.00");
+        assert_eq!(xlsx_cell(&entries, "C5").1.as_deref(), Some("s"));
+        assert_eq!(xlsx_cell(&entries, "E6").2, "10.25");
+
+        // Projects as rows, days as columns: the two INEK entries of the 9th add up,
+        // OTHER has no cell on the 9th
+        let pivot = xlsx_part(&xlsx, "xl/worksheets/sheet3.xml");
+        let (style, kind, value) = xlsx_cell(&pivot, "B1");
+        assert_eq!((kind, value), (None, serial.to_string()));
+        assert_eq!(format_code(&style), "dd.mm.");
+        assert_eq!(xlsx_cell(&pivot, "C1").2, (serial + 1).to_string());
+        let row = |r: u32| ["B", "C", "D"].map(|col| xlsx_cell(&pivot, &format!("{}{}", col, r)).2);
+        assert_eq!(row(2), ["7", "2", "9"]);
+        assert!(!pivot.contains(r#"r="B3""#));
+        assert_eq!(xlsx_cell(&pivot, "C3").2, "1.25");
+        assert_eq!(xlsx_cell(&pivot, "D3").2, "1.25");
+        assert_eq!(row(4), ["7", "3.25", "10.25"]);
+        assert!(pivot.contains("<f>SUM(B2:C2)</f>") && pivot.contains("<f>SUM(D2:D3)</f>"));
+
+        // An empty range still gives a valid workbook
+        assert!(range_xlsx(&config, DateRange::month(2024, 1).unwrap()).unwrap().starts_with(b"PK"));
+    }
+
+    #[test]
+    fn test_invoice_collect() {
+        let mut config = test_config();
+        config.project_records[0].client = Some(String::from("ACME"));
+        config.project_records[0].rate = Some(85.0);
+        let mut second = test_time_record();
+        second.id = 2;
+        second.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 12).unwrap();
+        second.project_entries[0].id = 2;
+        second.project_entries[0].hours = 1.25;
+        config.time_records.push(second);
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+
+        let invoice = Invoice::collect(&config, "ACME", DateRange::month(2025, 11).unwrap(), date).unwrap();
+        assert_eq!(invoice.number, "INV-0001");
+        assert_eq!(invoice.lines.len(), 1);
+        assert_eq!(invoice.lines[0].entries, vec![1, 2]);
+        assert_eq!(invoice.hours(), 4.75);
+        assert_eq!(invoice.net(), 403.75);
+        assert_eq!(invoice.vat(), 76.71);
+        assert_eq!(invoice.total(), 480.46);
+        assert!(String::from_utf8_lossy(&invoice_pdf(&invoice)).contains("(Invoice INV-0001)"));
+        assert!(invoice_html(&invoice).contains("480.46 EUR"));
+
+        // Billing an invoice with an entry billed in the meantime marks none of its entries
+        config.find_entry_mut(2).unwrap().invoice = Some(String::from("INV-0000"));
+        let err = bill(&mut config, &invoice).unwrap_err();
+        assert!(err.to_string().contains("#2 is already billed with invoice INV-0000"));
+        assert_eq!(config.find_entry(1).unwrap().1.invoice, None);
+        assert_eq!(config.settings.invoice.next_number, 1);
+        config.find_entry_mut(2).unwrap().invoice = None;
+
+        // Billed entries are not collected again
+        config.find_entry_mut(1).unwrap().invoice = Some(invoice.number.clone());
+        let rest = Invoice::collect(&config, "ACME", DateRange::all(), date).unwrap();
+        assert_eq!(rest.entry_ids().collect::<Vec<_>>(), vec![2]);
+        config.find_entry_mut(2).unwrap().invoice = Some(invoice.number.clone());
+        assert!(Invoice::collect(&config, "ACME", DateRange::all(), date).is_err());
+
+        // Projects without rate can't be billed
+        config.project_records[0].rate = None;
+        config.find_entry_mut(2).unwrap().invoice = None;
+        assert!(Invoice::collect(&config, "ACME", DateRange::all(), date).is_err());
+    }
+
+    #[test]
+    fn test_csv_import() {
+        let config = test_config();
+        let format = CsvFormat { delimiter: ';', decimal_separator: ',' };
+        let mut mapping = ColumnMapping::default();
+        mapping.set("date=Datum").unwrap();
+        let csv = "Datum;start;end;pause;project;hours;activity;tags\n\
+                   10.11.2025;08:00;16:30;0,5;INEK;4;Import #review;\n\
+                   10.11.2025;;;;INEK;2,5;More work;Meeting\n\
+                   2025-11-09;;;;INEK;2;Existing day;\n";
+
+        let plan = plan_import(&config, read_csv_rows(csv, &mapping, &format).unwrap()).unwrap();
+        assert_eq!(plan.new_days, vec![chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap()]);
+        assert_eq!(plan.entries, vec![2, 3, 4]);
+        assert_eq!(plan.tags, vec![String::from("meeting"), String::from("review")]);
+        assert_eq!(plan.records.len(), 2);
+        assert_eq!(plan.records[0].project_entries.len(), 2);
+        assert_eq!(plan.records[1].allocated_hours(), 6.5);
+
+        // All problems are reported and nothing is planned
+        let bad = "date,start,end,project,hours,activity\n\
+                   2025-11-11,,,INEK,1,No workday\n\
+                   2025-11-09,,,NOPE,1,Unknown project\n\
+                   2025-11-09,,,INEK,7,Too much\n";
+        let err = plan_import(&config, read_csv_rows(bad, &ColumnMapping::default(), &CsvFormat::default()).unwrap()).unwrap_err().to_string();
+        assert!(err.starts_with("3 problem(s)"));
+        assert!(err.contains("line 2:") && err.contains("line 3: unknown project code NOPE") && err.contains("line 4:"));
+
+        // Rows in a locked period are refused like the data menus refuse them
+        let mut config = config;
+        let locked_at = chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        config.locks.locks.push(PeriodLock {
+            period: String::from("2025-11"),
+            from: chrono::NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            to: chrono::NaiveDate::from_ymd_opt(2025, 11, 30).unwrap(),
+            locked_at,
+        });
+        let err = plan_import(&config, read_csv_rows(csv, &mapping, &format).unwrap()).unwrap_err().to_string();
+        assert!(err.starts_with("3 problem(s)"));
+        assert!(err.contains("line 4: 2025-11-09 lies in the locked period 2025-11 (submitted 2025-12-01 10:00)"));
+    }
+
+    #[test]
+    fn test_clockify_round_trip() {
+        let config = test_config();
+        let csv = clockify_csv(&config, DateRange::all()).unwrap();
+        assert!(csv.contains("INEK,,I ran a test,,,testing,Yes,11/09/2025,08:00:00,11/09/2025,11:30:00,03:30:00"));
+
+        let entries = read_tracker_csv(&csv).unwrap();
+        let mut target = test_config();
+        target.time_records.clear();
+        let options = TrackerImport::default();
+        let codes = map_projects(&mut target, &entries, &options).unwrap();
+        let (rows, left_out) = tracker_rows(&target, &entries, &codes, &options);
+        let plan = plan_import(&target, rows).unwrap();
+
+        assert_eq!(left_out, LeftOut::default());
+        let entry = &plan.records[0].project_entries[0];
+        assert_eq!((entry.project_name.code.as_str(), entry.hours, entry.activity.as_str()), ("INEK", 3.5, "I ran a test"));
+        assert_eq!(entry.tags, vec![String::from("testing")]);
+    }
+
+    #[test]
+    fn test_toggl_json_import() {
+        let json = r#"[
+            {"description": "Sprint planning", "project": "Website Relaunch", "client": "ACME", "start": "2025-11-10T09:00:00", "stop": "2025-11-10T10:30:00", "tags": ["Team Meeting"]},
+            {"description": "Build", "project": "Website Relaunch", "start": "2025-11-10T11:00:00", "end": "2025-11-10T13:00:00", "tags": []}
+        ]"#;
+        let entries = read_tracker_json(json).unwrap();
+        let mut config = test_config();
+
+        let err = map_projects(&mut config, &entries, &TrackerImport::default()).unwrap_err().to_string();
+        assert!(err.contains("'Website Relaunch'"));
+
+        let options = TrackerImport { create: true, ..TrackerImport::default() };
+        let codes = map_projects(&mut config, &entries, &options).unwrap();
+        assert_eq!(codes["Website Relaunch"], "WEBSI");
+        assert_eq!(config.project_records.last().unwrap().client.as_deref(), Some("ACME"));
+        assert_eq!(derive_code("Website", &config.project_records), "WEBS2");
+
+        let (rows, _) = tracker_rows(&config, &entries, &codes, &options);
+        let plan = plan_import(&config, rows).unwrap();
+        let day = plan.records.iter().find(|r| r.date == chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap()).unwrap();
+        assert_eq!((day.start_time.to_string().as_str(), day.end_time.to_string().as_str(), day.pause_minutes), ("09:00:00", "13:00:00", 0.5));
+        assert_eq!(day.allocated_hours(), 3.5);
+        assert_eq!(day.project_entries[0].tags, vec![String::from("team-meeting")]);
+    }
+
+    #[test]
+    fn test_range_ics() {
+        let dir = std::env::temp_dir().join(format!("ptt-ics-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.t_path = dir.join("data.json");
+        config.p_path = dir.join("projects.json");
+        config.s_path = dir.join("settings.json");
+        config.journal = Journal::open(&config.t_path, Snapshot::default()).unwrap();
+        config.time_records[0].project_entries[0].activity = "Tested; import, export\nand more ".repeat(4);
+        let ics = range_ics(&mut config, DateRange::all()).unwrap();
+
+        // The UID domain is made up once for the data and saved
+        let domain = config.settings.uid_domain.clone().unwrap();
+        assert!(domain.ends_with(".ptt") && domain.len() == 20);
+        let replayed = Journal::open(&config.t_path, Snapshot::default()).unwrap();
+        assert_eq!(replayed.base.settings.uid_domain, Some(domain.clone()));
+        assert_eq!(range_ics(&mut config, DateRange::all()).unwrap().matches(&domain).count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains(&format!("UID:record-1@{}\r\n", domain)) && ics.contains(&format!("UID:entry-1@{}\r\n", domain)));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("SUMMARY:INEK\r\n"));
+        assert!(ics.contains(r"DESCRIPTION:Tested\; import\, export\nand more"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(!ics.replace("\r\n", "").contains('\n'));
+
+        // A workday past midnight ends on the next day
+        let record = &mut config.time_records[0];
+        record.start_time = chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        record.end_time = chrono::NaiveTime::from_hms_opt(2, 0, 0).unwrap();
+        let ics = range_ics(&mut config, DateRange::all()).unwrap();
+        let events = parse_ics(&ics, DateRange::all()).unwrap();
+        assert_eq!(events[0].end - events[0].start, chrono::TimeDelta::hours(4));
+    }
+
+    #[test]
+    fn test_parse_ics_suggestions() {
+        let mut config = test_config();
+        config.project_records.push(Project { code: String::from("WEB"), allocation: 1.0, internal: false, rate: None, client: None });
+        config.settings.calendar_rules.push(CalendarRule { keyword: Some(String::from("standup")), organizer: None, project: String::from("WEB") });
+        let ics = [
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT", "UID:a", "SUMMARY:INEK review", "DTSTART:20251109T090000", "DTEND:20251109T103000",
+            r"DESCRIPTION:Tested\; import\, expo", " rt", "END:VEVENT",
+            "BEGIN:VEVENT", "UID:b", "SUMMARY:Daily Standup", "DTSTART:20251103T100000", "DTEND:20251103T101500",
+            "RRULE:FREQ=WEEKLY;COUNT=3", "EXDATE:20251110T100000", "END:VEVENT",
+            "BEGIN:VEVENT", "UID:c", "SUMMARY:Cancelled", "STATUS:CANCELLED", "DTSTART:20251109T120000", "DTEND:20251109T130000", "END:VEVENT",
+            "BEGIN:VEVENT", "UID:d", "SUMMARY:Holiday", "DTSTART;VALUE=DATE:20251109", "DTEND;VALUE=DATE:20251110", "END:VEVENT",
+            "END:VCALENDAR",
+        ].join("\r\n");
+        let events = parse_ics(&ics, DateRange::month(2025, 11).unwrap()).unwrap();
+
+        let starts: Vec<String> = events.iter().map(|e| format!("{} {}", e.uid, e.start)).collect();
+        assert_eq!(starts, vec!["b 2025-11-03 10:00:00", "a 2025-11-09 09:00:00", "b 2025-11-17 10:00:00"]);
+        assert_eq!(events[1].description, "Tested; import, export");
+
+        let suggestion = suggest_entry(&config, &events[1]).unwrap();
+        assert_eq!((suggestion.project.as_deref(), suggestion.hours), (Some("INEK"), 1.5));
+        assert_eq!(suggest_project(&config, &events[0]).as_deref(), Some("WEB"));
+        assert!(suggest_entry(&config, &events[0]).is_err());
+
+        config.time_records[0].project_entries[0].hours = 9.0;
+        assert_eq!(suggest_entry(&config, &events[1]).unwrap().hours, 0.5);
+
+        // A 4 h day with 3.9 h recorded has less than a quarter left
+        config.time_records[0].end_time = chrono::NaiveTime::from_hms_opt(12, 30, 0).unwrap();
+        config.time_records[0].project_entries[0].hours = 3.9;
+        assert_eq!(config.time_records[0].get_net_hours(), 4.0);
+        assert_eq!(suggest_entry(&config, &events[1]).unwrap_err(), "no hours left to record on this day");
+    }
+
+    #[test]
+    fn test_timew_and_timeclock_round_trip() {
+        let config = test_config();
+        let ledger = timeclock(&config, DateRange::all());
+        assert_eq!(ledger, "i 2025/11/09 08:00:00 INEK  I ran a test\no 2025/11/09 11:30:00\n");
+
+        let timew = timew_json(&config, DateRange::all()).unwrap();
+        let from_timew = read_timew_json(&timew, |tag| tag == "INEK").unwrap();
+        let from_ledger = read_timeclock(&format!("; comment\n{}", ledger)).unwrap();
+        for entries in [from_timew, from_ledger] {
+            let entry = &entries[0];
+            assert_eq!((entry.project.as_str(), entry.description.as_str()), ("INEK", "I ran a test"));
+            assert_eq!((entry.end - entry.start).num_minutes(), 210);
+        }
+
+        let tags_first = r#"[{"start": "20251110T080000Z", "end": "20251110T090000Z", "tags": ["review", "INEK"]}]"#;
+        let entries = read_timew_json(tags_first, |tag| tag == "INEK").unwrap();
+        assert_eq!((entries[0].project.as_str(), entries[0].tags.clone()), ("INEK", vec![String::from("review")]));
+
+        let entries = read_timeclock("i 2025/11/10 09:00:00 Clients:ACME:Web  Build\no 2025/11/10 10:00:00\n").unwrap();
+        assert_eq!((entries[0].project.as_str(), entries[0].client.as_str()), ("Clients:ACME:Web", "Clients:ACME"));
+        let err = read_timeclock("o 2025/11/10 10:00:00\ni 2025/11/10 11:00:00 Web\n").unwrap_err().to_string();
+        assert!(err.contains("line 1: clocked out without clocking in") && err.contains("line 2: still clocked in"));
+    }
+
+    #[test]
+    fn test_entries_across_midnight() {
+        let mut config = test_config();
+        let record = &mut config.time_records[0];
+        record.start_time = chrono::NaiveTime::from_hms_opt(20, 0, 0).unwrap();
+        record.end_time = chrono::NaiveTime::from_hms_opt(23, 55, 0).unwrap();
+        record.pause_minutes = 0.0;
+        record.project_entries[0].hours = 2.0;
+        let mut late = record.project_entries[0].clone();
+        late.id = 2;
+        record.project_entries.push(late);
+        let slots: Vec<String> = record.entry_slots().iter().map(|(s, e)| format!("{} {}", s, e)).collect();
+        assert_eq!(slots, vec!["2025-11-09 20:00:00 2025-11-09 22:00:00", "2025-11-09 22:00:00 2025-11-10 00:00:00"]);
+
+        // The exports carry the end over to the next day
+        assert!(timeclock(&config, DateRange::all()).ends_with("i 2025/11/09 22:00:00 INEK  I ran a test\no 2025/11/10 00:00:00\n"));
+        let csv = clockify_csv(&config, DateRange::all()).unwrap();
+        assert!(csv.lines().next().unwrap().ends_with(",End Time,Duration,Duration (decimal)"));
+        assert!(csv.contains("11/09/2025,22:00:00,11/10/2025,00:00:00,02:00:00,2.00"));
+        assert!(org_file(&config, DateRange::all()).contains("CLOCK: [2025-11-09 Sun 22:00]--[2025-11-10 Mon 00:00] =>  2:00"));
+
+        // Imported entries are split at midnight, overlaps only count once
+        let ledger = "i 2025/11/10 22:00:00 INEK  Deploy\no 2025/11/11 01:30:00\n\
+            i 2025/11/12 09:00:00 INEK  Review\no 2025/11/12 11:00:00\n\
+            i 2025/11/12 10:00:00 INEK  Call\no 2025/11/12 12:00:00\n\
+            i 2025/11/12 10:15:00 INEK  Inside\no 2025/11/12 10:45:00\n";
+        let entries = read_timeclock(ledger).unwrap();
+        let options = TrackerImport::default();
+        let codes = map_projects(&mut config, &entries, &options).unwrap();
+        let (rows, left_out) = tracker_rows(&config, &entries, &codes, &options);
+        assert_eq!(left_out, LeftOut { short: 0, overlapped: vec![String::from("line 4: Inside (INEK, 2025-11-12 10:15 to 10:45) lies within line 3")] });
+        let days: Vec<String> = rows.iter().map(|r| format!("{} {:?}-{:?} {:?} {}", r.date, r.start, r.end, r.hours, r.activity)).collect();
+        assert_eq!(days, vec![
+            "2025-11-10 Some(22:00:00)-Some(23:59:00) Some(2.0) Deploy",
+            "2025-11-11 Some(00:00:00)-Some(01:30:00) Some(1.5) Deploy",
+            "2025-11-12 Some(09:00:00)-Some(12:00:00) Some(2.0) Review",
+            "2025-11-12 None-None Some(1.0) Call",
+        ]);
+        let plan = plan_import(&config, rows).unwrap();
+        assert_eq!(plan.new_days.len(), 3);
+    }
+
+    #[test]
+    fn test_org_round_trip() {
+        let mut config = test_config();
+        config.time_records[0].project_entries[0].tags = vec![String::from("code-review")];
+        let org = org_file(&config, DateRange::all());
+        assert!(org.starts_with("* 2025-11-09 Sun\n** I ran a test :code_review:\n   :PROPERTIES:\n   :PROJECT:  INEK\n"));
+        assert!(org.contains("CLOCK: [2025-11-09 Sun 08:00]--[2025-11-09 Sun 11:30] =>  3:30\n"));
+
+        let entries = read_org(&org, |word| word == "INEK").unwrap();
+        assert_eq!((entries[0].project.as_str(), entries[0].description.as_str()), ("INEK", "I ran a test"));
+        assert_eq!(entries[0].tags, vec![String::from("code_review")]);
+        assert_eq!((entries[0].end - entries[0].start).num_minutes(), 210);
+
+        let notes = "* Work :client:\n** TODO [#A] INEK Sprint planning :meeting:\n:LOGBOOK:\nCLOCK: [2025-11-10 Mon 09:00]--[2025-11-10 Mon 10:30] =>  1:30\n:END:\n** Other stuff\nCLOCK: [2025-11-10 Mon 11:00]\n";
+        let err = read_org(notes, |word| word == "INEK").unwrap_err().to_string();
+        assert!(err.contains("line 7: the clock is still running"));
+
+        let entries = read_org(&notes.replace("CLOCK: [2025-11-10 Mon 11:00]\n", ""), |word| word == "INEK").unwrap();
+        assert_eq!((entries[0].project.as_str(), entries[0].description.as_str()), ("INEK", "Sprint planning"));
+        assert_eq!(entries[0].tags, vec![String::from("client"), String::from("meeting")]);
+    }
+
+    #[test]
+    fn test_journal_diff_and_replay() {
+        let mut config = test_config();
+        let base = Snapshot::of(&config);
+
+        let mut second = test_time_record();
+        second.id = 2;
+        second.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        second.project_entries[0].id = 2;
+        config.time_records.push(second);
+        let mut extra = config.time_records[0].project_entries[0].clone();
+        extra.id = 3;
+        config.time_records[0].project_entries.insert(0, extra);
+        config.time_records[0].project_entries[1].hours = 2.0;
+        config.time_records[0].pause_minutes = 1.0;
+        config.project_records.retain(|p| p.code != "Vacation");
+        config.settings.add_tags(&[String::from("review")]);
+
+        let changes = diff(&base, &Snapshot::of(&config));
+        let events: Vec<&str> = changes.iter().map(|c| match c {
+            Change::RecordAdded { .. } => "record_added",
+            Change::RecordEdited { .. } => "record_edited",
+            Change::EntryAdded { .. } => "entry_added",
+            Change::EntryEdited { .. } => "entry_edited",
+            Change::EntriesOrdered { .. } => "entries_ordered",
+            Change::ProjectDeleted { .. } => "project_deleted",
+            Change::SettingsChanged { .. } => "settings_changed",
+            _ => "other",
+        }).collect();
+        assert_eq!(events, vec!["record_edited", "entry_added", "entry_edited", "entries_ordered", "record_added", "project_deleted", "settings_changed"]);
+
+        // Replaying twice gives the same state, like after a compaction cut off before the journal was emptied
+        let mut replayed = base.clone();
+        for _ in 0..2 {
+            for change in &changes {
+                change.apply(&mut replayed);
+            }
+        }
+        assert_eq!(replayed, Snapshot::of(&config));
+        assert!(diff(&replayed, &Snapshot::of(&config)).is_empty());
+
+        let dir = std::env::temp_dir().join(format!("ptt-journal-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(JOURNAL_FILE);
+        let at = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let line = serde_json::to_string(&JournalEvent { at, change: Change::RecordDeleted { id: 2 } }).unwrap();
+        assert_eq!(line, r#"{"at":"2025-11-10T09:00:00","event":"record_deleted","id":2}"#);
+        std::fs::write(&path, format!("{}\n{{\"at\":\"2025-11-1", line)).unwrap();
+        assert_eq!(read_journal(&path).unwrap().len(), 1);
+
+        // Saving after a cut-off write drops the broken line instead of continuing it
+        let mut journal = Journal::open(&dir.join("data.json"), replayed).unwrap();
+        journal.append(vec![Change::RecordDeleted { id: 1 }]).unwrap();
+        let events = read_journal(&path).unwrap();
+        assert_eq!(events.iter().map(|e| e.change.clone()).collect::<Vec<_>>(), vec![Change::RecordDeleted { id: 2 }, Change::RecordDeleted { id: 1 }]);
+        std::fs::write(&path, "{\"at\":").unwrap();
+        journal.append(vec![Change::RecordDeleted { id: 3 }]).unwrap();
+        assert_eq!(read_journal(&path).unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let dir = std::env::temp_dir().join(format!("ptt-undo-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.t_path = dir.join("data.json");
+        config.journal = Journal::open(&config.t_path, Snapshot::of(&config)).unwrap();
+        config.undo = UndoStack::open(&config.t_path).unwrap();
+        config.tombstones = Tombstones::open(&config.t_path).unwrap();
+        assert_eq!(config.undo().unwrap(), None);
+
+        // A deleted workday keeps its history
+        let date = config.time_records[0].date;
+        config.reason = Some(String::from("booked twice"));
+        config.time_records.clear();
+        config.save().unwrap();
+        let tombstones = Tombstones::open(&config.t_path).unwrap();
+        let lines = history_lines(&tombstones.records[0]);
+        assert_eq!(lines[0], "Workday 2025-11-09 08:00-18:00, pause 0.5");
+        assert!(lines[1].ends_with("workday removed: 2025-11-09 08:00-18:00, pause 0.5 (booked twice)"));
+        assert!(lines[2].ends_with("entry #1 removed: INEK 3.5 h: I ran a test (booked twice)"));
+        assert!(print_history(&config, date).is_ok());
+        config.settings.undo_depth = 1;
+        config.project_records.retain(|p| p.code != "Vacation");
+        config.save().unwrap();
+        assert_eq!(config.undo.undo.len(), 1);
+
+        let label = config.undo().unwrap().unwrap();
+        assert!(label.starts_with("project Vacation deleted, settings changed"));
+        assert!(config.project_records.iter().any(|p| p.code == "Vacation"));
+        assert!(config.time_records.is_empty());
+
+        let reloaded = UndoStack::open(&config.t_path).unwrap();
+        assert_eq!((reloaded.undo.len(), reloaded.redo.len()), (0, 1));
+        let replayed = Journal::open(&config.t_path, Snapshot::of(&test_config())).unwrap();
+        assert_eq!(replayed.base, Snapshot::of(&config));
+
+        config.redo().unwrap();
+        assert!(!config.project_records.iter().any(|p| p.code == "Vacation"));
+
+        // Turning undo off drops the history, a save made while it is off can't be redone over
+        config.undo().unwrap();
+        config.set_undo_depth(0).unwrap();
+        assert!(UndoStack::open(&config.t_path).unwrap().is_empty());
+        config.undo.redo.push(UndoStep { at: chrono::NaiveDateTime::MIN, undo: vec![], redo: vec![] });
+        config.undo.write().unwrap();
+        config.time_records.push(test_time_record());
+        config.save().unwrap();
+        assert!(UndoStack::open(&config.t_path).unwrap().is_empty());
+        assert!(config.undo().unwrap_err().to_string().contains("Undo is turned off"));
+        assert!(config.redo().is_err());
+
+        // Ids of deleted or undone workdays and entries aren't handed out again, also after a reload
+        config.set_undo_depth(5).unwrap();
+        let deleted = config.time_records.pop().unwrap();
+        config.save().unwrap();
+        assert!(config.next_record_id() > deleted.id);
+        let mut record = test_time_record();
+        record.id = config.next_record_id();
+        record.project_entries[0].id = config.next_entry_id();
+        config.time_records.push(record.clone());
+        config.save().unwrap();
+        config.undo().unwrap();
+        assert!(config.time_records.is_empty());
+        assert_eq!(config.next_record_id(), record.id + 1);
+        assert_eq!(config.next_entry_id(), record.project_entries[0].id + 1);
+        let replayed = Journal::open(&config.t_path, Snapshot::of(&test_config())).unwrap();
+        assert_eq!(replayed.base.settings.last_record_id, record.id);
+
+        // Undo and redo keep the history of the changes and add their own revision
+        config.redo().unwrap();
+        config.time_records[0].project_entries[0].hours = 2.0;
+        config.save().unwrap();
+        config.undo().unwrap();
+        let entry = &config.time_records[0].project_entries[0];
+        assert_eq!(entry.hours, record.project_entries[0].hours);
+        let reasons: Vec<Option<String>> = entry.history.iter().map(|r| r.reason.clone()).collect();
+        assert_eq!(reasons, vec![None, Some(String::from("undone"))]);
+        assert_eq!(entry.history[1].changes[0].to_string(), format!("hours: 2 -> {}", record.project_entries[0].hours));
+        config.redo().unwrap();
+        assert_eq!(config.time_records[0].project_entries[0].history.len(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_revisions() {
+        let mut config = test_config();
+        let mut extra = config.time_records[0].project_entries[0].clone();
+        extra.id = 2;
+        config.time_records[0].project_entries.push(extra);
+        let base = Snapshot::of(&config);
+
+        config.time_records[0].start_time = chrono::NaiveTime::from_hms_opt(7, 30, 0).unwrap();
+        config.time_records[0].project_entries[0].hours = 4.0;
+        config.time_records[0].project_entries.retain(|e| e.id != 2);
+        record_revisions(&base, &mut config, Some(String::from("forgot the review")));
+
+        let record = &config.time_records[0];
+        let changes: Vec<String> = record.history[0].changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(changes, vec!["start: 08:00 -> 07:30", "entry #2 removed: INEK 3.5 h: I ran a test"]);
+        assert_eq!(record.project_entries[0].history[0].changes[0].to_string(), "hours: 3.5 -> 4");
+
+        let lines = history_lines(record);
+        assert_eq!(lines[0], "Workday 2025-11-09 07:30-18:00, pause 0.5");
+        assert!(lines[1].ends_with("start: 08:00 -> 07:30 (forgot the review)"));
+        assert_eq!(lines[3], "Entry #1 INEK 4 h: I ran a test");
+
+        // The history survives the journal
+        let mut replayed = base.clone();
+        for change in diff(&base, &Snapshot::of(&config)) {
+            change.apply(&mut replayed);
+        }
+        assert_eq!(replayed, Snapshot::of(&config));
+
+        let report = MonthlyReport::build(&config, 2025, 11).unwrap();
+        let pdf = timesheet_pdf(&report, &config.settings.timesheet, true);
+        assert!(String::from_utf8_lossy(&pdf).contains("(Change history November 2025)"));
+    }
+
+    #[test]
+    fn test_period_locks() {
+        let date = |d: u32| chrono::NaiveDate::from_ymd_opt(2025, 11, d).unwrap();
+        assert_eq!(parse_period("2025-w45").unwrap(), (String::from("2025-W45"), DateRange::new(date(3), date(9))));
+        assert_eq!(parse_period("2025-2").unwrap().0, "2025-02");
+        assert!(parse_period("2025-13").is_err() && parse_period("2025-W54").is_err());
+
+        let mut config = test_config();
+        config.journal.base = Snapshot::of(&config);
+        config.locks.lock("2025-11").unwrap();
+        assert!(config.locks.lock("2025-11").is_err());
+        assert!(config.check_unlocked(date(9)).is_err());
+        assert!(config.check_unlocked(chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap()).is_ok());
+
+        let report = MonthlyReport::build(&config, 2025, 11).unwrap();
+        assert!(render_monthly(&report, OutputFormat::Text).contains("\nLocked: 2025-11 (submitted "));
+
+        // Saving a change in the locked month is refused and resets the data
+        config.time_records[0].project_entries[0].hours = 5.0;
+        config.settings.add_tags(&[String::from("review")]);
+        let err = config.persist().unwrap_err().to_string();
+        assert!(err.contains("2025-11-09 lies in the locked period 2025-11"));
+        assert_eq!(config.time_records[0].project_entries[0].hours, 3.5);
+        assert!(!config.settings.tags.contains(&String::from("review")));
+
+        assert!(config.locks.unlock("2025-11", " ").is_err());
+        config.locks.unlock("2025-11", "Customer asked for a correction").unwrap();
+        assert!(config.check_unlocked(date(9)).is_ok());
+        let log: Vec<String> = config.locks.log.iter().map(|e| e.to_string()).collect();
+        assert!(log[0].ends_with("2025-11 locked"));
+        assert!(log[1].ends_with("2025-11 unlocked: Customer asked for a correction"));
+    }
+
+    #[test]
+    fn test_invoice_locked_period() {
+        let dir = std::env::temp_dir().join(format!("ptt-invoice-lock-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.project_records[0].client = Some(String::from("ACME"));
+        config.project_records[0].rate = Some(85.0);
+        config.t_path = dir.join("data.json");
+        config.journal = Journal::open(&config.t_path, Snapshot::of(&config)).unwrap();
+        config.undo = UndoStack::open(&config.t_path).unwrap();
+        config.locks = Locks::open(&config.t_path).unwrap();
+        config.seals = Seals::open(&config.t_path).unwrap();
+        config.lock_period("2025-11").unwrap();
+
+        // Billing a submitted month doesn't change its hours, so it passes the lock and the seal
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+        let invoice = Invoice::collect(&config, "ACME", DateRange::month(2025, 11).unwrap(), date).unwrap();
+        let out = dir.join("INV-0001.html");
+        issue_invoice(&mut config, &invoice, InvoiceFormat::Html, &out).unwrap();
+        assert!(out.exists());
+        assert_eq!(config.find_entry(1).unwrap().1.invoice.as_deref(), Some("INV-0001"));
+        assert_eq!(config.settings.invoice.next_number, 2);
+        assert!(verify_seals(&config).unwrap().is_empty());
+
+        // Billing again fails and leaves no invoice file behind
+        let again = dir.join("again.html");
+        assert!(issue_invoice(&mut config, &invoice, InvoiceFormat::Html, &again).is_err());
+        assert!(!again.exists());
+
+        // Other changes in the month are still refused
+        config.find_entry_mut(1).unwrap().hours = 5.0;
+        assert!(config.persist().unwrap_err().to_string().contains("locked period 2025-11"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_seal_chain() {
+        assert_eq!(sha256_hex(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+
+        let mut config = test_config();
+        let lock = config.locks.lock("2025-11").unwrap().clone();
+        config.seals.seal(&lock, &config.time_records).unwrap();
+        let lock = config.locks.lock("2025-W47").unwrap().clone();
+        config.seals.seal(&lock, &config.time_records).unwrap();
+        assert_eq!(config.seals.seals[0].prev, GENESIS);
+        assert_eq!(config.seals.seals[1].prev, config.seals.seals[0].hash);
+        assert_eq!(config.seals.seals[0].days.len(), 1);
+        assert!(verify_seals(&config).unwrap().is_empty());
+
+        // Changes behind the lock, e.g. in the data files, are reported by day
+        config.time_records[0].project_entries[0].hours = 5.0;
+        let mut added = test_time_record();
+        added.id = 2;
+        added.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 20).unwrap();
+        config.time_records.push(added);
+        let problems: Vec<String> = verify_seals(&config).unwrap().iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems, vec![
+            "2025-11: 2025-11-09 was changed after sealing",
+            "2025-11: 2025-11-20 was added after sealing",
+            "2025-W47: 2025-11-20 was added after sealing",
+        ]);
+
+        // Unlocked periods aren't compared anymore, but their seal stays in the chain
+        config.locks.unlock("2025-11", "Corrected hours").unwrap();
+        config.locks.unlock("2025-W47", "Corrected hours").unwrap();
+        assert!(verify_seals(&config).unwrap().is_empty());
+        config.seals.seals[0].days.clear();
+        assert_eq!(verify_seals(&config).unwrap(), vec![
+            SealProblem::BrokenChain { period: String::from("2025-11") },
+        ]);
+    }
+
+    #[test]
+    fn test_concurrent_instances() {
+        let dir = std::env::temp_dir().join(format!("ptt-instance-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let lock_path = dir.join(INSTANCE_LOCK_FILE);
+        let lock = InstanceLock::acquire(&lock_path).unwrap();
+        let err = InstanceLock::acquire(&lock_path).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("Another ptt (pid {}, started ", std::process::id())));
+        drop(lock);
+        assert!(!lock_path.exists());
+
+        // A lock file nobody holds the file lock on is left behind, whatever pid it names
+        std::fs::write(&lock_path, r#"{"pid":1,"started":"2025-11-01T08:00:00"}"#).unwrap();
+        let lock = InstanceLock::acquire(&lock_path).unwrap();
+        let holder: LockHolder = serde_json::from_str(&std::fs::read_to_string(&lock_path).unwrap()).unwrap();
+        assert_eq!(holder.pid, std::process::id());
+        drop(lock);
+        assert!(!lock_path.exists());
+
+        // Two instances on the same files, each adding a workday and changing the same entry
+        let open = || {
+            let mut config = test_config();
+            config.t_path = dir.join("data.json");
+            config.p_path = dir.join("projects.json");
+            config.s_path = dir.join("settings.json");
+            config.journal = Journal::open(&config.t_path, Snapshot::of(&test_config())).unwrap();
+            config.undo = UndoStack::open(&config.t_path).unwrap();
+            config.on_disk = Some(config.disk_stamp());
+            config
+        };
+        open().compact().unwrap();
+        let mut first = open();
+        let mut second = open();
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+        for (config, hours) in [(&mut first, 1.0), (&mut second, 2.0)] {
+            let mut record = test_time_record();
+            record.id = config.next_record_id();
+            record.date = date;
+            record.project_entries[0].id = config.next_entry_id();
+            record.project_entries[0].hours = hours;
+            config.time_records.push(record);
+            config.time_records[0].project_entries[0].hours = hours;
+        }
+        first.save().unwrap();
+        second.save().unwrap();
+
+        assert_eq!(second.time_records.len(), 2);
+        let added = second.time_records.iter().find(|r| r.date == date).unwrap();
+        let ids: Vec<(u64, f64)> = added.project_entries.iter().map(|e| (e.id, e.hours)).collect();
+        assert_eq!(ids, vec![(2, 1.0), (3, 2.0)]);
+        assert_eq!(second.time_records[0].project_entries[0].hours, 2.0);
+        let replayed = Journal::open(&second.t_path, Snapshot::of(&test_config())).unwrap();
+        assert_eq!(replayed.base, Snapshot::of(&second));
+
+        // The first one picks the changes of the second up with its next save
+        first.settings.undo_depth = 5;
+        first.save().unwrap();
+        assert_eq!(first.time_records, second.time_records);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_sync() {
+        let dir = std::env::temp_dir().join(format!("ptt-git-test-{}", std::process::id()));
+        let remote = dir.join("remote.git");
+        std::fs::create_dir_all(&remote).unwrap();
+        let status = std::process::Command::new("git").args(["init", "--bare", "--quiet"]).current_dir(&remote).status();
+        if !status.is_ok_and(|s| s.success()) {
+            return;
+        }
+        let open = |name: &str, config: Config| {
+            let data = dir.join(name);
+            std::fs::create_dir_all(&data).unwrap();
+            let mut config = config;
+            config.t_path = data.join("data.json");
+            config.p_path = data.join("projects.json");
+            config.s_path = data.join("settings.json");
+            config.journal = Journal::open(&config.t_path, Snapshot::of(&config)).unwrap();
+            config.undo = UndoStack::open(&config.t_path).unwrap();
+            config.locks = Locks::open(&config.t_path).unwrap();
+            config.seals = Seals::open(&config.t_path).unwrap();
+            config.tombstones = Tombstones::open(&config.t_path).unwrap();
+            config.on_disk = Some(config.disk_stamp());
+            config
+        };
+        let mut laptop = open("laptop", test_config());
+        let mut later = test_time_record();
+        later.id = 2;
+        later.date = chrono::NaiveDate::from_ymd_opt(2025, 11, 20).unwrap();
+        later.project_entries[0].id = 2;
+        laptop.time_records.insert(0, later);
+        git_init(&mut laptop, Some(remote.display().to_string())).unwrap();
+        assert!(sync(&mut laptop).unwrap().is_empty());
+
+        // Workdays are written by date
+        let written = std::fs::read_to_string(&laptop.t_path).unwrap();
+        assert!(written.find("2025-11-09").unwrap() < written.find("2025-11-20").unwrap());
+
+        // The desktop starts empty and gets the data of the remote
+        let mut empty = test_config();
+        empty.time_records.clear();
+        let mut desktop = open("desktop", empty);
+        git_init(&mut desktop, Some(remote.display().to_string())).unwrap();
+        assert_eq!(desktop.time_records.len(), 2);
+        // The remote stays with the machine, the synced settings don't name it
+        assert_eq!(desktop.git_remote(), Some(remote.display().to_string()));
+        assert!(!std::fs::read_to_string(&desktop.s_path).unwrap().contains("remote"));
+
+        desktop.time_records[0].project_entries[0].hours = 4.0;
+        desktop.save().unwrap();
+        let log = std::process::Command::new("git").args(["log", "-1", "--format=%s"]).current_dir(desktop.data_dir()).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&log.stdout).trim(), "entry #1 changed (INEK 4 h)");
+        sync(&mut desktop).unwrap();
+
+        let pulled: Vec<String> = sync(&mut laptop).unwrap().iter().filter(|c| c.is_shown()).map(|c| c.to_string()).collect();
+        assert_eq!(pulled, vec!["entry #1 changed (INEK 4 h)"]);
+        assert_eq!(laptop.time_records, desktop.time_records);
+
+        // Both add days with the same new ids, next to each other in the file and apart
+        let add_day = |config: &mut Config, month: u32, day: u32| {
+            let mut record = test_time_record();
+            record.id = config.next_record_id();
+            record.date = chrono::NaiveDate::from_ymd_opt(2025, month, day).unwrap();
+            record.project_entries[0].id = config.next_entry_id();
+            config.time_records.push(record);
+            config.save().unwrap();
+        };
+        add_day(&mut laptop, 11, 21);
+        add_day(&mut laptop, 12, 1);
+        add_day(&mut desktop, 11, 22);
+        add_day(&mut desktop, 10, 1);
+        sync(&mut desktop).unwrap();
+        let pulled: Vec<String> = sync(&mut laptop).unwrap().iter().filter(|c| c.is_shown()).map(|c| c.to_string()).collect();
+        assert_eq!(pulled, vec!["workday 2025-10-01 added", "workday 2025-11-22 added", "workdays reordered"]);
+        let pulled: Vec<String> = sync(&mut desktop).unwrap().iter().map(|c| c.to_string()).collect();
+        assert_eq!(pulled.iter().filter(|c| c.ends_with(" added")).count(), 2);
+        assert_eq!(laptop.time_records, desktop.time_records);
+
+        let mut record_ids: Vec<u64> = laptop.time_records.iter().map(|r| r.id).collect();
+        let mut entry_ids: Vec<u64> = laptop.time_records.iter().flat_map(|r| r.project_entries.iter().map(|e| e.id)).collect();
+        let dates: Vec<String> = laptop.time_records.iter().map(|r| r.date.to_string()).collect();
+        assert_eq!(dates, vec!["2025-10-01", "2025-11-09", "2025-11-20", "2025-11-21", "2025-11-22", "2025-12-01"]);
+        record_ids.sort();
+        record_ids.dedup();
+        entry_ids.sort();
+        entry_ids.dedup();
+        assert_eq!((record_ids.len(), entry_ids.len()), (6, 6));
+
+        // The merge is a commit with both histories as parents, the files hold the merged data
+        let parents = std::process::Command::new("git").args(["log", "-1", "--format=%P %s"]).current_dir(laptop.data_dir()).output().unwrap();
+        let parents = String::from_utf8_lossy(&parents.stdout);
+        assert_eq!(parents.split_whitespace().take(2).count(), 2);
+        assert!(parents.contains("Merge the data of"));
+        let written: Vec<TimeRecord> = serde_json::from_str(&std::fs::read_to_string(&desktop.t_path).unwrap()).unwrap();
+        assert_eq!(written, desktop.time_records);
+
+        // Locks and seals come from the side that changed them
+        laptop.lock_period("2025-10").unwrap();
+        sync(&mut laptop).unwrap();
+        add_day(&mut desktop, 12, 2);
+        sync(&mut desktop).unwrap();
+        assert!(desktop.check_unlocked(chrono::NaiveDate::from_ymd_opt(2025, 10, 1).unwrap()).is_err());
+        assert_eq!(desktop.seals.seals.len(), 1);
+        assert!(verify_seals(&desktop).unwrap().is_empty());
+
+        // A change of a day the other side locked stops the sync before merging
+        sync(&mut laptop).unwrap();
+        laptop.lock_period("2025-11").unwrap();
+        sync(&mut laptop).unwrap();
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 11, 20).unwrap();
+        desktop.time_records.iter_mut().find(|r| r.date == date).unwrap().pause_minutes = 1.0;
+        desktop.save().unwrap();
+        let err = sync(&mut desktop).unwrap_err().to_string();
+        assert!(err.contains("would change 2025-11-20, which is locked"));
+        assert!(desktop.check_unlocked(date).is_ok());
+        let status = std::process::Command::new("git").args(["status", "--porcelain"]).current_dir(desktop.data_dir()).output().unwrap();
+        assert!(status.stdout.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_update_project_entry_by_id() {
+        let dir = std::env::temp_dir().join(format!("ptt-entry-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.t_path = dir.join("data.json");
+        config.journal = Journal::open(&config.t_path, Snapshot::of(&config)).unwrap();
+        config.undo = UndoStack::open(&config.t_path).unwrap();
+
+        assert!(config.update_project_entry(7, Some(1.0), None, None).is_err());
+        // 9.5 h net, so the entry can have at most 9.5 h
+        assert!(config.update_project_entry(1, Some(9.75), None, None).is_err());
+        config.update_project_entry(1, Some(9.5), None, Some(vec![String::from("review")])).unwrap();
+        let (_, entry) = config.find_entry(1).unwrap();
+        assert_eq!((entry.hours, entry.activity.as_str()), (9.5, "I ran a test"));
+        assert_eq!(entry.tags, vec![String::from("review")]);
+        assert!(config.settings.tags.contains(&String::from("review")));
+
+        config.find_entry_mut(1).unwrap().invoice = Some(String::from("INV-0001"));
+        let err = config.update_project_entry(1, None, Some(String::from("changed")), None).unwrap_err();
+        assert!(err.to_string().contains("billed with invoice INV-0001"));
+        // Nor can the day with a billed entry be deleted
+        let err = config.delete_time_record(1).unwrap_err();
+        assert!(err.to_string().contains("#1 billed with invoice INV-0001"));
+        assert!(config.find_record(1).is_some());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use serde_json::{Value, json};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::ics::utc_stamp;
+use crate::import::{parse_import_date, parse_import_time};
+use crate::trackers::{TrackedEntry, TrackerImport, is_known_name, tag_name};
+
+/// Parses a Timewarrior timestamp like 20251109T070000Z into local time
+fn timew_time(value: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .map(|t| Utc.from_utc_datetime(&t).with_timezone(&Local).naive_local())
+        .map_err(|_| format!("'{}' is not a timestamp like 20251109T070000Z", value))
+}
+
+/// Reads the output of `timew export`. Timewarrior only knows tags, so the project is the first
+/// tag that has a project code already, else the first tag. The other tags stay tags
+pub fn read_timew_json(contents: &str, is_project: impl Fn(&str) -> bool) -> Result<Vec<TrackedEntry>> {
+    let value: Value = serde_json::from_str(contents).context("The file is not valid JSON")?;
+    let items = value.as_array().context("No intervals found, expected the output of timew export")?;
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let parsed = (|| -> Result<TrackedEntry, String> {
+            let start = item.get("start").and_then(Value::as_str).ok_or("start is missing")?;
+            let end = item.get("end").and_then(Value::as_str).ok_or("end is missing, is the interval still open?")?;
+            let mut tags: Vec<String> = item
+                .get("tags")
+                .and_then(Value::as_array)
+                .map(|tags| tags.iter().filter_map(Value::as_str).map(String::from).collect())
+                .unwrap_or_default();
+            let project = match tags.iter().position(|t| is_project(t)) {
+                Some(index) => tags.remove(index),
+                None if !tags.is_empty() => tags.remove(0),
+                None => String::new(),
+            };
+            Ok(TrackedEntry {
+                start: timew_time(start)?,
+                end: timew_time(end)?,
+                project,
+                client: String::new(),
+                description: item.get("annotation").and_then(Value::as_str).unwrap_or_default().to_string(),
+                tags: tags.iter().map(|t| tag_name(t)).collect(),
+            })
+        })();
+        match parsed {
+            Ok(entry) => entries.push(entry),
+            Err(msg) => errors.push(format!("interval {}: {}", i + 1, msg)),
+        }
+    }
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    Ok(entries)
+}
+
+/// Reads a `timew export` file, with the names known to the import as projects
+pub fn read_timew_file(config: &Config, options: &TrackerImport, path: &Path) -> Result<Vec<TrackedEntry>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {:#?}", path))?;
+    read_timew_json(&contents, |tag| is_known_name(config, options, tag))
+}
+
+/// Parses the date and time of a timeclock line like `2025/11/09 08:00:00`
+fn timeclock_time(date: &str, time: &str) -> Result<NaiveDateTime, String> {
+    let date = parse_import_date(&date.replace('/', "-"))?;
+    Ok(date.and_time(parse_import_time(time)?))
+}
+
+/// Reads a ledger timeclock file with lines like `i 2025/11/09 08:00:00 Clients:ACME  Review`
+/// and `o 2025/11/09 11:30:00`. The account is the project, its parent account the client,
+/// the text after two spaces the description
+pub fn read_timeclock(contents: &str) -> Result<Vec<TrackedEntry>> {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut open: Option<(usize, TrackedEntry)> = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        let mut fields = line.splitn(4, ' ');
+        let kind = fields.next().unwrap_or_default();
+        if !["i", "o", "O"].contains(&kind) {
+            // Comments, empty lines and other ledger directives
+            continue;
+        }
+        let (date, time) = (fields.next().unwrap_or_default(), fields.next().unwrap_or_default());
+        let time = match timeclock_time(date, time) {
+            Ok(time) => time,
+            Err(msg) => {
+                errors.push(format!("line {}: {}", line_number, msg));
+                continue;
+            }
+        };
+        let rest = fields.next().unwrap_or_default().trim();
+
+        if kind == "i" {
+            if let Some((opened, _)) = &open {
+                errors.push(format!("line {}: clocked in again without clocking out of line {}", line_number, opened));
+            }
+            let (account, description) = match rest.split_once("  ").or_else(|| rest.split_once('\t')) {
+                Some((account, description)) => (account.trim(), description.trim()),
+                None => (rest, ""),
+            };
+            let client = account.rsplit_once(':').map(|(client, _)| client).unwrap_or_default();
+            open = Some((line_number, TrackedEntry {
+                start: time,
+                end: time,
+                project: account.to_string(),
+                client: client.to_string(),
+                description: description.to_string(),
+                tags: vec![],
+            }));
+        } else {
+            match open.take() {
+                Some((_, mut entry)) if time >= entry.start => {
+                    entry.end = time;
+                    entries.push(entry);
+                },
+                Some(_) => errors.push(format!("line {}: clocked out before clocking in", line_number)),
+                None => errors.push(format!("line {}: clocked out without clocking in", line_number)),
+            }
+        }
+    }
+    if let Some((opened, _)) = open {
+        errors.push(format!("line {}: still clocked in", opened));
+    }
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    Ok(entries)
+}
+
+/// Reads a ledger timeclock file
+pub fn read_timeclock_file(path: &Path) -> Result<Vec<TrackedEntry>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {:#?}", path))?;
+    read_timeclock(&contents)
+}
+
+/// Records of a date range by date
+fn records_in(config: &Config, range: DateRange) -> Vec<&TimeRecord> {
+    let mut records: Vec<&TimeRecord> = config.time_records.iter().filter(|r| range.contains(r.date)).collect();
+    records.sort_by_key(|r| r.date);
+    records
+}
+
+/// Entries of a date range as input for `timew import`, with the time slots of
+/// `TimeRecord::entry_slots`. The project code is the first tag
+pub fn timew_json(config: &Config, range: DateRange) -> Result<String> {
+    let mut intervals = Vec::new();
+    for record in records_in(config, range) {
+        for (entry, (start, end)) in record.project_entries.iter().zip(record.entry_slots()) {
+            let mut tags = vec![entry.project_name.code.clone()];
+            tags.extend(entry.tags.iter().cloned());
+            let mut interval = json!({
+                "id": intervals.len() + 1,
+                "start": utc_stamp(start),
+                "end": utc_stamp(end),
+                "tags": tags,
+            });
+            if !entry.activity.is_empty() {
+                interval["annotation"] = json!(entry.activity);
+            }
+            intervals.push(interval);
+        }
+    }
+    serde_json::to_string_pretty(&intervals).context("Failed to write JSON")
+}
+
+/// Entries of a date range as ledger timeclock file, with the project code as account
+pub fn timeclock(config: &Config, range: DateRange) -> String {
+    let mut out = String::new();
+    for record in records_in(config, range) {
+        for (entry, (start, end)) in record.project_entries.iter().zip(record.entry_slots()) {
+            let activity = entry.activity.split_whitespace().collect::<Vec<_>>().join(" ");
+            let payee = if activity.is_empty() { String::new() } else { format!("  {}", activity) };
+            out.push_str(&format!("i {} {}{}\n", start.format("%Y/%m/%d %H:%M:%S"), entry.project_name.code, payee));
+            out.push_str(&format!("o {}\n", end.format("%Y/%m/%d %H:%M:%S")));
+        }
+    }
+    out
+}
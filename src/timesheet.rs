@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use inquire::{Confirm, Text};
+use anyhow::Result;
+
+use crate::models::*;
+use crate::pdf::{Font, PdfDocument, wrap_text};
+use crate::reports::MonthlyReport;
+use crate::export::write_bytes;
+use crate::utils::{choose_month, choose_year};
+use crate::history::history_lines;
+use crate::locks::lock_status;
+
+const LEFT: f64 = PdfDocument::MARGIN;
+const RIGHT: f64 = PdfDocument::WIDTH - PdfDocument::MARGIN;
+const SIZE: f64 = 9.0;
+const LINE: f64 = 12.0;
+/// Left edge of the project entries column
+const ENTRIES_X: f64 = 300.0;
+/// Height for rows below the table header of a page
+const PAGE_ROWS_HEIGHT: f64 = PdfDocument::HEIGHT - 2.0 * PdfDocument::MARGIN - LINE - 4.0;
+
+fn table_header(doc: &mut PdfDocument) {
+    let y = doc.next_line(LINE + 4.0);
+    doc.text(LEFT, y, SIZE, Font::Bold, "Date");
+    doc.text(125.0, y, SIZE, Font::Bold, "Start");
+    doc.text(165.0, y, SIZE, Font::Bold, "End");
+    doc.text_right(245.0, y, SIZE, Font::Bold, "Pause");
+    doc.text_right(285.0, y, SIZE, Font::Bold, "Net");
+    doc.text(ENTRIES_X, y, SIZE, Font::Bold, "Project entries");
+    doc.line(LEFT, y - 3.0, RIGHT, y - 3.0, 0.8);
+}
+
+/// Lays out the signed monthly timesheet: header, one row per day with its entries,
+/// totals per project and signature lines for employee and customer.
+/// With `history` the changes of the days follow on an extra page
+pub fn timesheet_pdf(report: &MonthlyReport, header: &TimesheetHeader, history: bool) -> Vec<u8> {
+    let mut doc = PdfDocument::new();
+
+    let y = doc.next_line(18.0);
+    doc.text(LEFT, y, 16.0, Font::Bold, &format!("Timesheet {}", report.title()));
+    doc.next_line(8.0);
+    for (label, value) in [("Name", &header.name), ("Employee no.", &header.employee_number), ("Customer", &header.customer)] {
+        if value.is_empty() {
+            continue;
+        }
+        let y = doc.next_line(LINE + 2.0);
+        doc.text(LEFT, y, 10.0, Font::Bold, label);
+        doc.text(LEFT + 90.0, y, 10.0, Font::Regular, value);
+    }
+    if !report.locks.is_empty() {
+        let y = doc.next_line(LINE + 2.0);
+        doc.text(LEFT, y, 10.0, Font::Regular, &lock_status(&report.locks));
+    }
+    doc.next_line(10.0);
+
+    table_header(&mut doc);
+    for day in &report.days {
+        let mut lines: Vec<String> = Vec::new();
+        for entry in &day.project_entries {
+            let text = format!("{} {:.2} h: {}", entry.project_name.code, entry.hours, entry.activity);
+            lines.extend(wrap_text(&text, RIGHT - ENTRIES_X, SIZE, Font::Regular));
+        }
+        if lines.is_empty() {
+            lines.push(String::from("-"));
+        }
+
+        // Keep a day on one page if it fits on one, a longer day continues line by line.
+        // The header is repeated on every new page
+        let height = LINE * lines.len() as f64 + 4.0;
+        if doc.y - height < PdfDocument::MARGIN && height <= PAGE_ROWS_HEIGHT {
+            doc.new_page();
+            table_header(&mut doc);
+        }
+        for (i, line) in lines.iter().enumerate() {
+            if doc.y - LINE < PdfDocument::MARGIN {
+                doc.new_page();
+                table_header(&mut doc);
+            }
+            let y = doc.next_line(LINE);
+            if i == 0 {
+                doc.text(LEFT, y, SIZE, Font::Regular, &day.date.format("%a %d.%m.%Y").to_string());
+                doc.text(125.0, y, SIZE, Font::Regular, &day.start_time.format("%H:%M").to_string());
+                doc.text(165.0, y, SIZE, Font::Regular, &day.end_time.format("%H:%M").to_string());
+                doc.text_right(245.0, y, SIZE, Font::Regular, &format!("{:.2}", day.pause_minutes));
+                doc.text_right(285.0, y, SIZE, Font::Regular, &format!("{:.2}", day.get_net_hours()));
+            }
+            doc.text(ENTRIES_X, y, SIZE, Font::Regular, line);
+        }
+        let bottom = doc.next_line(4.0);
+        doc.line(LEFT, bottom + 1.0, RIGHT, bottom + 1.0, 0.3);
+    }
+    let y = doc.next_line(LINE + 2.0);
+    doc.text(LEFT, y, SIZE, Font::Bold, "Total");
+    doc.text_right(285.0, y, SIZE, Font::Bold, &format!("{:.2}", report.net_total()));
+    doc.text(ENTRIES_X, y, SIZE, Font::Bold, &format!("{:.2} h allocated", report.total()));
+
+    doc.next_line(16.0);
+    let y = doc.next_line(LINE + 4.0);
+    doc.text(LEFT, y, 11.0, Font::Bold, "Totals per project");
+    for (group, rows) in [("", &report.projects), (" (internal)", &report.internal)] {
+        for (code, hours) in rows.iter() {
+            let y = doc.next_line(LINE);
+            doc.text(LEFT, y, SIZE, Font::Regular, &format!("{}{}", code, group));
+            doc.text_right(285.0, y, SIZE, Font::Regular, &format!("{:.2} h", hours));
+        }
+    }
+
+    // Signature lines, moved to a new page if they do not fit anymore
+    doc.next_line(50.0);
+    let y = doc.next_line(LINE + 10.0);
+    let middle = PdfDocument::WIDTH / 2.0;
+    doc.line(LEFT, y + 10.0, middle - 25.0, y + 10.0, 0.6);
+    doc.line(middle + 25.0, y + 10.0, RIGHT, y + 10.0, 0.6);
+    doc.text(LEFT, y, 8.0, Font::Regular, "Date, signature employee");
+    let customer = if header.customer.is_empty() { String::from("Date, signature customer") } else { format!("Date, signature {}", header.customer) };
+    doc.text(middle + 25.0, y, 8.0, Font::Regular, &customer);
+
+    let changes: Vec<String> = report.days.iter().flat_map(history_lines).collect();
+    if history && !changes.is_empty() {
+        doc.new_page();
+        let y = doc.next_line(18.0);
+        doc.text(LEFT, y, 14.0, Font::Bold, &format!("Change history {}", report.title()));
+        doc.next_line(6.0);
+        for change in changes {
+            let font = if change.starts_with(' ') { Font::Regular } else { Font::Bold };
+            for line in wrap_text(&change, RIGHT - LEFT, SIZE, font) {
+                let y = doc.next_line(LINE);
+                doc.text(LEFT, y, SIZE, font, &line);
+            }
+        }
+    }
+
+    doc.to_bytes()
+}
+
+/// Menu entry to create the PDF timesheet of a month
+pub fn timesheet_menu(config: &Config) -> Result<()> {
+
+    let Some(year) = choose_year(config, "Please choose a year for the timesheet")? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let Some(month) = choose_month(config, "Please choose a month for the timesheet", year)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let default_name = format!("timesheet-{}-{:02}.pdf", year, month.month_number);
+    let Some(path) = Text::new("File name:")
+        .with_default(&default_name)
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let report = MonthlyReport::build(config, year, month.month_number)?;
+    let changed = report.days.iter().any(|d| !history_lines(d).is_empty());
+    let history = changed && Confirm::new("Add the change history of the month?")
+        .with_default(false)
+        .prompt_skippable()?
+        .unwrap_or(false);
+    write_bytes(&timesheet_pdf(&report, &config.settings.timesheet, history), Path::new(&path))
+}
+
+/// Lets the user edit the header of the timesheet
+pub fn edit_timesheet_header(config: &mut Config) -> Result<()> {
+
+    let current = config.settings.timesheet.clone();
+    let mut fields = Vec::new();
+    for (prompt, value) in [("Your name:", &current.name), ("Employee number:", &current.employee_number), ("Customer:", &current.customer)] {
+        let Some(answer) = Text::new(prompt).with_default(value).prompt_skippable()? else {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(());
+        };
+        fields.push(answer);
+    }
+
+    let [name, employee_number, customer]: [String; 3] = fields.try_into().expect("three prompts");
+    config.settings.timesheet = TimesheetHeader { name, employee_number, customer };
+    config.save()?;
+    Ok(())
+}
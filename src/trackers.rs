@@ -0,0 +1,501 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use inquire::{Confirm, Select, Text};
+use serde_json::Value;
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::import::{ImportRow, parse_import_date, parse_import_time, plan_import};
+use crate::timeclock::{read_timeclock_file, read_timew_file};
+use crate::org::read_org_file;
+
+/// Name used for entries without project or client
+pub const NO_PROJECT: &str = "(none)";
+
+/// A time entry exported from another tracker like Toggl Track, Clockify or Timewarrior
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedEntry {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub project: String,
+    pub client: String,
+    pub description: String,
+    pub tags: Vec<String>
+}
+
+/// Options of a Toggl or Clockify import
+#[derive(Debug, Clone, Default)]
+pub struct TrackerImport {
+    /// Project codes for names, given by the user
+    pub mapping: BTreeMap<String, String>,
+    /// Map clients instead of projects
+    pub by_client: bool,
+    /// Create projects for names without code
+    pub create: bool
+}
+
+/// Tags of the other trackers may contain spaces, ours are single words
+pub fn tag_name(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').split_whitespace().collect::<Vec<_>>().join("-").to_lowercase()
+}
+
+/// Reads the detailed report CSV of Toggl Track or Clockify. Both name the columns alike,
+/// e.g. `Start date`, `Start time`, `End date` (Toggl also `Stop date`), `Project`, `Client`, `Description`, `Tags`
+pub fn read_tracker_csv(contents: &str) -> Result<Vec<TrackedEntry>> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers: HashMap<String, usize> = reader
+        .headers()
+        .context("Failed to read the CSV header")?
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.trim().trim_start_matches('\u{feff}').to_lowercase(), i))
+        .collect();
+    let column = |names: &[&str]| names.iter().find_map(|n| headers.get(*n).copied());
+    let (Some(start_date), Some(start_time)) = (column(&["start date"]), column(&["start time"])) else {
+        bail!("The file has no 'Start date' and 'Start time' columns of a Toggl or Clockify export");
+    };
+    let end_date = column(&["end date", "stop date"]);
+    let end_time = column(&["end time", "stop time"]).context("The file has no 'End time' column")?;
+    let project = column(&["project"]);
+    let client = column(&["client"]);
+    let description = column(&["description"]);
+    let tags = column(&["tags"]);
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let line = i + 2;
+        let record = record.with_context(|| format!("Failed to read line {}", line))?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).map(str::trim).unwrap_or_default();
+
+        let parsed = (|| -> Result<TrackedEntry, String> {
+            let date = parse_import_date(field(Some(start_date)))?;
+            let start = date.and_time(parse_import_time(field(Some(start_time)))?);
+            let end_day = match field(end_date) {
+                "" => date,
+                value => parse_import_date(value)?,
+            };
+            let end = end_day.and_time(parse_import_time(field(Some(end_time)))?);
+            Ok(TrackedEntry {
+                start,
+                end,
+                project: field(project).to_string(),
+                client: field(client).to_string(),
+                description: field(description).to_string(),
+                tags: field(tags).split(',').map(tag_name).filter(|t| !t.is_empty()).collect(),
+            })
+        })();
+        match parsed {
+            Ok(entry) => entries.push(entry),
+            Err(msg) => errors.push(format!("line {}: {}", line, msg)),
+        }
+    }
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    Ok(entries)
+}
+
+/// Looks up the first string found under one of the keys. Keys may be paths like `timeInterval.start`
+fn json_str(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| {
+        key.split('.')
+            .try_fold(value, |v, k| v.get(k))
+            .and_then(Value::as_str)
+            .map(String::from)
+    })
+}
+
+/// Parses a timestamp like 2025-11-09T08:00:00+01:00 into local time
+fn json_time(value: &str) -> Result<NaiveDateTime, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Local).naive_local())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .map_err(|_| format!("'{}' is not a timestamp like 2025-11-09T08:00:00+01:00", value))
+}
+
+/// Reads the JSON exports of Toggl Track (detailed report or time entries) and Clockify
+/// (detailed report or time entries). Entries are either the top level array or below
+/// `data`, `timeentries` or `time_entries`
+pub fn read_tracker_json(contents: &str) -> Result<Vec<TrackedEntry>> {
+    let value: Value = serde_json::from_str(contents).context("The file is not valid JSON")?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(map) => ["data", "timeentries", "timeEntries", "time_entries"]
+            .iter()
+            .find_map(|k| map.get(*k).and_then(Value::as_array))
+            .context("No time entries found in the JSON file")?,
+        _ => bail!("No time entries found in the JSON file"),
+    };
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let parsed = (|| -> Result<TrackedEntry, String> {
+            let start = json_str(item, &["start", "timeInterval.start"]).ok_or("start is missing")?;
+            // Running timers have no end yet
+            let end = json_str(item, &["end", "stop", "timeInterval.end"]).ok_or("end is missing, is the timer still running?")?;
+            let tags = item
+                .get("tags")
+                .or_else(|| item.get("tagNames"))
+                .and_then(Value::as_array)
+                .map(|tags| tags.iter().filter_map(|t| t.as_str().or_else(|| t.get("name").and_then(Value::as_str))).map(tag_name).collect())
+                .unwrap_or_default();
+            Ok(TrackedEntry {
+                start: json_time(&start)?,
+                end: json_time(&end)?,
+                project: json_str(item, &["project", "project_name", "projectName", "project.name"]).unwrap_or_default(),
+                client: json_str(item, &["client", "client_name", "clientName", "project.clientName"]).unwrap_or_default(),
+                description: json_str(item, &["description"]).unwrap_or_default(),
+                tags,
+            })
+        })();
+        match parsed {
+            Ok(entry) => entries.push(entry),
+            Err(msg) => errors.push(format!("entry {}: {}", i + 1, msg)),
+        }
+    }
+    if !errors.is_empty() {
+        bail!("{} problem(s) found, nothing was imported:\n{}", errors.len(), errors.join("\n"));
+    }
+    Ok(entries)
+}
+
+/// Reads a Toggl Track or Clockify export, JSON or CSV by the file extension
+pub fn read_tracker_file(path: &Path) -> Result<Vec<TrackedEntry>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {:#?}", path))?;
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json")) {
+        read_tracker_json(&contents)
+    } else {
+        read_tracker_csv(&contents)
+    }
+}
+
+/// Name an entry is mapped by
+fn mapped_name<'a>(entry: &'a TrackedEntry, options: &TrackerImport) -> &'a str {
+    let name = if options.by_client { &entry.client } else { &entry.project };
+    if name.is_empty() { NO_PROJECT } else { name }
+}
+
+/// Derives an unused project code of at most 5 characters from a name
+pub fn derive_code(name: &str, taken: &[Project]) -> String {
+    let base: String = name.chars().filter(|c| c.is_ascii_alphanumeric()).take(5).collect::<String>().to_uppercase();
+    let base = if base.is_empty() { String::from("PRJ") } else { base };
+    let free = |code: &str| !taken.iter().any(|p| p.code.eq_ignore_ascii_case(code));
+    if free(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n: u32| {
+            let suffix = n.to_string();
+            format!("{}{}", base.chars().take(5 - suffix.len()).collect::<String>(), suffix)
+        })
+        .find(|code| free(code))
+        .expect("a free code exists")
+}
+
+/// Finds the project code for each name of the entries: given mapping, remembered mapping of
+/// earlier imports, then a project with that code. Missing projects are created on request.
+/// Returns:
+///     The code of every name, or an error listing the names without code
+pub fn map_projects(config: &mut Config, entries: &[TrackedEntry], options: &TrackerImport) -> Result<BTreeMap<String, String>> {
+    let names: BTreeSet<&str> = entries.iter().map(|e| mapped_name(e, options)).collect();
+    let mut codes = BTreeMap::new();
+    let mut missing = Vec::new();
+
+    for name in names {
+        let known = options.mapping.get(name)
+            .or_else(|| config.settings.project_mapping.get(name))
+            .cloned()
+            .or_else(|| config.project_records.iter().find(|p| p.code.eq_ignore_ascii_case(name)).map(|p| p.code.clone()));
+        match known {
+            Some(code) if config.project_records.iter().any(|p| p.code == code) => {
+                codes.insert(name.to_string(), code);
+            },
+            Some(code) => bail!("'{}' is mapped to the unknown project code {}", name, code),
+            None if options.create && name != NO_PROJECT => {
+                let code = derive_code(name, &config.project_records);
+                let client = entries
+                    .iter()
+                    .find(|e| mapped_name(e, options) == name && !e.client.is_empty())
+                    .map(|e| e.client.clone());
+                println!("New project {} for '{}'", code, name);
+                config.project_records.push(Project { code: code.clone(), allocation: 0.0, internal: false, rate: None, client });
+                codes.insert(name.to_string(), code);
+            },
+            None => missing.push(name.to_string()),
+        }
+    }
+
+    if !missing.is_empty() {
+        bail!(
+            "No project code for {}. Map them like --map '{}=CODE' or create them with --create",
+            missing.iter().map(|n| format!("'{}'", n)).collect::<Vec<_>>().join(", "),
+            missing[0]
+        );
+    }
+    Ok(codes)
+}
+
+/// Entries of another tracker a tracker import leaves out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LeftOut {
+    /// Number of entries shorter than 8 minutes
+    pub short: usize,
+    /// Entries lying within earlier ones, whose time is recorded already
+    pub overlapped: Vec<String>
+}
+
+impl LeftOut {
+    pub fn print(&self) {
+        if self.short > 0 {
+            println!("{} entries shorter than 8 minutes are left out", self.short);
+        }
+        if !self.overlapped.is_empty() {
+            println!("{} entries lie within earlier ones and are left out:", self.overlapped.len());
+            for entry in &self.overlapped {
+                println!("  {}", entry);
+            }
+        }
+    }
+}
+
+/// Line, entry, start and end of a part of an entry on one date
+type Piece<'a> = (usize, &'a TrackedEntry, NaiveDateTime, NaiveDateTime);
+
+/// Splits the entries by date at midnight and cuts off the time they overlap an earlier entry,
+/// so every minute counts once
+/// Returns:
+///     The pieces by date and the entries lying within earlier ones
+fn day_pieces(entries: &[TrackedEntry]) -> (BTreeMap<NaiveDate, Vec<Piece<'_>>>, Vec<String>) {
+    let mut sorted: Vec<(usize, &TrackedEntry)> = entries.iter().enumerate().map(|(i, e)| (i + 1, e)).collect();
+    sorted.sort_by_key(|(_, e)| e.start);
+
+    let mut days: BTreeMap<NaiveDate, Vec<_>> = BTreeMap::new();
+    let mut overlapped = Vec::new();
+    let (mut covered, mut covered_by) = (NaiveDateTime::MIN, 0);
+    for (line, entry) in sorted {
+        let mut start = entry.start.max(covered);
+        if entry.start < entry.end && entry.end <= covered {
+            overlapped.push(format!(
+                "line {}: {} ({}, {} to {}) lies within line {}",
+                line, entry.description, entry.project, entry.start.format("%Y-%m-%d %H:%M"), entry.end.format("%H:%M"), covered_by
+            ));
+            continue;
+        }
+        if entry.end > covered {
+            (covered, covered_by) = (entry.end, line);
+        }
+        // Entries without time keep an empty piece, which is left out as too short
+        if entry.end <= start {
+            days.entry(start.date()).or_default().push((line, entry, start, start));
+            continue;
+        }
+        while start.date() < entry.end.date() {
+            let midnight = start.date().succ_opt().expect("valid date").and_time(NaiveTime::MIN);
+            days.entry(start.date()).or_default().push((line, entry, start, midnight));
+            start = midnight;
+        }
+        if start < entry.end {
+            days.entry(start.date()).or_default().push((line, entry, start, entry.end));
+        }
+    }
+    (days, overlapped)
+}
+
+/// Groups the entries into workdays. Entries running past midnight are split by date and
+/// overlapping ones only count the time not recorded before. A new day runs from its first
+/// start to its last end, the gaps between the entries become the pause. Hours are rounded to quarters
+/// Returns:
+///     The rows to import and the entries left out
+pub fn tracker_rows(config: &Config, entries: &[TrackedEntry], codes: &BTreeMap<String, String>, options: &TrackerImport) -> (Vec<ImportRow>, LeftOut) {
+    let mut rows = Vec::new();
+    let (days, overlapped) = day_pieces(entries);
+    let mut left_out = LeftOut { short: 0, overlapped };
+    for (date, day) in days {
+        let hours: Vec<f64> = day.iter().map(|(_, _, start, end)| TimeRecord::round_quarter((*end - *start).num_seconds() as f64 / 3600.0)).collect();
+
+        let mut workday = if config.time_records.iter().any(|r| r.date == date) {
+            (None, None, None)
+        } else {
+            let start = day.iter().map(|(_, _, start, _)| *start).min().expect("days are not empty");
+            // Pieces up to midnight end the day at its last minute
+            let last_minute = date.and_time(NaiveTime::from_hms_opt(23, 59, 0).expect("valid time"));
+            let end = day.iter().map(|(_, _, _, end)| *end).max().expect("days are not empty").min(last_minute);
+            let span = TimeRecord::round_quarter((end - start).num_seconds() as f64 / 3600.0);
+            let pause = ((span - hours.iter().sum::<f64>()) * 4.0).floor().max(0.0) / 4.0;
+            (Some(start.time()), Some(end.time()), Some(pause))
+        };
+
+        for ((line, entry, _, _), hours) in day.into_iter().zip(hours) {
+            if hours <= 0.0 {
+                left_out.short += 1;
+                continue;
+            }
+            let (start, end, pause) = std::mem::take(&mut workday);
+            rows.push(ImportRow {
+                line,
+                date,
+                start,
+                end,
+                pause,
+                project: codes.get(mapped_name(entry, options)).cloned(),
+                hours: Some(hours),
+                activity: entry.description.clone(),
+                tags: entry.tags.clone(),
+            });
+        }
+    }
+    (rows, left_out)
+}
+
+/// Imports the entries of another tracker, or only previews them on a dry run
+pub fn import_entries(config: &mut Config, entries: &[TrackedEntry], options: &TrackerImport, dry_run: bool) -> Result<()> {
+    let mut staged = config.clone();
+    let codes = map_projects(&mut staged, entries, options)?;
+    let (rows, left_out) = tracker_rows(&staged, entries, &codes, options);
+    let plan = plan_import(&staged, rows)?;
+
+    plan.print_preview();
+    left_out.print();
+    if dry_run {
+        println!("Dry run, nothing was imported");
+        return Ok(());
+    }
+    config.project_records = staged.project_records;
+    config.settings.project_mapping.extend(codes);
+    plan.apply(config)
+}
+
+/// Imports a Toggl Track or Clockify export, or only previews it on a dry run
+pub fn import_tracker(config: &mut Config, path: &Path, options: &TrackerImport, dry_run: bool) -> Result<()> {
+    import_entries(config, &read_tracker_file(path)?, options, dry_run)
+}
+
+/// Whether a project or client name of another tracker has a project code already
+pub fn is_known_name(config: &Config, options: &TrackerImport, name: &str) -> bool {
+    options.mapping.contains_key(name)
+        || config.settings.project_mapping.contains_key(name)
+        || config.project_records.iter().any(|p| p.code.eq_ignore_ascii_case(name))
+}
+
+/// Menu entry to import from Toggl Track, Clockify, Timewarrior, a ledger timeclock or an Org file.
+/// Names without code are asked for
+pub fn import_tracker_menu(config: &mut Config) -> Result<()> {
+
+    let formats = vec!["Toggl Track or Clockify", "Timewarrior", "Ledger timeclock", "Org-mode clocks"];
+    let Some(format) = Select::new("Import from?", formats).prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let help = match format {
+        "Timewarrior" => "Output of timew export, as JSON",
+        "Ledger timeclock" => "File with i and o lines of ledger",
+        "Org-mode clocks" => "Org file with CLOCK lines below the headings",
+        _ => "Detailed report of Toggl Track or Clockify, as CSV or JSON",
+    };
+    let Some(path) = Text::new("File to import:")
+        .with_help_message(help)
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+    let entries = match format {
+        "Timewarrior" => read_timew_file(config, &TrackerImport::default(), Path::new(&path)),
+        "Ledger timeclock" => read_timeclock_file(Path::new(&path)),
+        "Org-mode clocks" => read_org_file(config, &TrackerImport::default(), Path::new(&path)),
+        _ => read_tracker_file(Path::new(&path)),
+    };
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+
+    let mut options = TrackerImport::default();
+    let mut staged = config.clone();
+    let names: BTreeSet<String> = entries.iter().map(|e| mapped_name(e, &options).to_string()).collect();
+    for name in names {
+        if is_known_name(config, &options, &name) {
+            continue;
+        }
+        let mut choices: Vec<String> = config.project_records.iter().map(|p| p.code.clone()).collect();
+        let create = format!("Create project {}", derive_code(&name, &staged.project_records));
+        choices.insert(0, create.clone());
+        let Some(choice) = Select::new(&format!("Which project is '{}'?", name), choices).prompt_skippable()? else {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(());
+        };
+        if choice == create {
+            let code = derive_code(&name, &staged.project_records);
+            staged.project_records.push(Project { code: code.clone(), allocation: 0.0, internal: false, rate: None, client: None });
+            options.mapping.insert(name, code);
+        } else {
+            options.mapping.insert(name, choice);
+        }
+    }
+
+    let codes = map_projects(&mut staged, &entries, &options)?;
+    let (rows, left_out) = tracker_rows(&staged, &entries, &codes, &options);
+    let plan = match plan_import(&staged, rows) {
+        Ok(plan) => plan,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    plan.print_preview();
+    left_out.print();
+
+    let confirm = Confirm::new("Import these entries?").prompt_skippable()?;
+    if confirm == Some(true) {
+        config.project_records = staged.project_records;
+        config.settings.project_mapping.extend(codes);
+        plan.apply(config)?;
+    } else {
+        println!("Nothing imported");
+    }
+    Ok(())
+}
+
+/// Entries of a date range in the CSV layout of the Clockify import, with the time slots
+/// of `TimeRecord::entry_slots`
+pub fn clockify_csv(config: &Config, range: DateRange) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "Project", "Client", "Description", "Task", "Email", "Tags", "Billable",
+        "Start Date", "Start Time", "End Date", "End Time", "Duration", "Duration (decimal)",
+    ])?;
+
+    let mut records: Vec<&TimeRecord> = config.time_records.iter().filter(|r| range.contains(r.date)).collect();
+    records.sort_by_key(|r| r.date);
+    for record in records {
+        for (entry, (start, end)) in record.project_entries.iter().zip(record.entry_slots()) {
+            let code = &entry.project_name.code;
+            let client = config.project_records.iter().find(|p| &p.code == code).and_then(|p| p.client.clone()).unwrap_or_default();
+            let minutes = (entry.hours * 60.0).round() as i64;
+            writer.write_record([
+                code.clone(),
+                client,
+                entry.activity.clone(),
+                String::new(),
+                String::new(),
+                entry.tags.join(", "),
+                String::from(if config.is_internal(code) { "No" } else { "Yes" }),
+                start.format("%m/%d/%Y").to_string(),
+                start.format("%H:%M:%S").to_string(),
+                end.format("%m/%d/%Y").to_string(),
+                end.format("%H:%M:%S").to_string(),
+                format!("{:02}:{:02}:00", minutes / 60, minutes % 60),
+                format!("{:.2}", entry.hours),
+            ])?;
+        }
+    }
+    let bytes = writer.into_inner().context("Failed to write CSV")?;
+    String::from_utf8(bytes).context("CSV is not valid UTF-8")
+}
@@ -2,6 +2,22 @@ use std::vec;
 
 use crate::models::*;
 use crate::utils::*;
+use crate::reports::*;
+use crate::query::query_report;
+use crate::search::search_menu;
+use crate::export::{choose_csv_format, export_csv_menu};
+use crate::render::render_monthly_menu;
+use crate::timesheet::{edit_timesheet_header, timesheet_menu};
+use crate::xlsx::export_xlsx_menu;
+use crate::ics::{calendar_rules_menu, export_ics_menu, import_ics_menu};
+use crate::import::import_csv_menu;
+use crate::trackers::import_tracker_menu;
+use crate::undo::{edit_undo_depth, redo_menu, undo_menu};
+use crate::history::history_menu;
+use crate::locks::locks_menu;
+use crate::instance::release_and_exit;
+use crate::git::git_menu;
+use crate::invoice::{create_invoice_menu, edit_invoice_settings, edit_rate_and_client};
 //use crate::storage::*;
 use inquire::{Confirm, Select};
 use anyhow::Result;
@@ -10,7 +26,7 @@ use anyhow::Result;
 /// Main function of the code. Gets called by main.rs
 pub fn run(config: &mut Config) -> Result<()> {
 
-    if config.project_records.is_empty() {
+    if config.projects().next().is_none() {
         let confirm = Confirm::new("There are currently no projects! Would you like to add one first? (Y/n): ").prompt();
 
         if matches!(confirm, Ok(true)) {
@@ -27,14 +43,20 @@ pub fn run(config: &mut Config) -> Result<()> {
 /// Main menu of the App. Always where the user starts
 pub fn main_menu(config: &mut Config) -> Result<()> {
     // get the menu structure via a vec
-    let menu_options = vec!["Log Time", "Projects", "Reports", "Exit"];
+    let menu_options = vec!["Log Time", "Search", "Projects", "Tags", "Reports", "Invoices", "Undo", "Redo", "Settings", "Exit"];
     loop {
         if let Ok(ans) = Select::new("What do you want to do?", menu_options.clone()).prompt()  {
             
             match ans {
                 "Log Time" => log_time_menu(config)?,
+                "Search" => search_menu(config)?,
                 "Projects" => projects_menu(config)?,
+                "Tags" => tags_menu(config)?,
                 "Reports" => reports_menu(config)?,
+                "Invoices" => invoices_menu(config)?,
+                "Undo" => undo_menu(config)?,
+                "Redo" => redo_menu(config)?,
+                "Settings" => settings_menu(config)?,
                 "Exit" => {
                     println!("Goodbye!");
                     break Ok(());
@@ -50,7 +72,7 @@ pub fn log_time_menu(config: &mut Config) -> Result<()> {
 
     // TODO: Read in Values beforehand
 
-    let log_time_options = vec!["Record Workday", "Record Project Work" ,"Edit Workday Record", "Delete Workday","Back", "Exit"];
+    let log_time_options = vec!["Record Workday", "Record Project Work" ,"Edit Workday Record", "Delete Project Entry", "Delete Workday", "Import CSV", "Import from other Trackers", "Import Calendar", "Back", "Exit"];
 
     loop {
 
@@ -59,8 +81,9 @@ pub fn log_time_menu(config: &mut Config) -> Result<()> {
                 "Record Workday" => record_time_record(config)?, // If Record gets overwritten, loses all entries for project_entries up so far
                 "Record Project Work" => record_project_work(config)?,
                 "Edit Workday Record" => edit_workday_record(config)?,
+                "Delete Project Entry" => delete_workday_entry(config)?,
                 "Delete Workday" => {
-                    let (selected_date, _) = match choose_date(config, "Which record would you like to delete?") {
+                    let (_, selected_record) = match choose_date(config, "Which record would you like to delete?") {
                         Ok(Some(value)) => value,
                         Ok(None) => {
                             println!("Operation cancelled. Returning to main...");
@@ -68,12 +91,15 @@ pub fn log_time_menu(config: &mut Config) -> Result<()> {
                         },
                         Err(e) => return Err(e),
                     };
-                    config.delete_time_record(selected_date)?;
+                    config.delete_time_record(selected_record.id)?;
                 },
+                "Import CSV" => import_csv_menu(config)?,
+                "Import from other Trackers" => import_tracker_menu(config)?,
+                "Import Calendar" => import_ics_menu(config)?,
                 "Back" => break Ok(()),
                 "Exit" => {
                     println!("Goodbye");
-                    std::process::exit(0)},
+                    release_and_exit(0)},
                 &_ => todo!()
             }
         }
@@ -86,10 +112,11 @@ pub fn projects_menu(config: &mut Config) -> Result<()>{
     println!("Yay, let's work with som projects");
 
     loop {
-        let options = vec!["List Projects", "Add Project", "Delete Project", "Back", "Exit"];
+        let options = vec!["List Projects", "Add Project", "Add Category", "Delete Project", "Back", "Exit"];
         match Select::new("Project Menu", options).prompt() {
             Ok("List Projects") => list_projects(config)?,
             Ok("Add Project") => add_project(config)?,            
+            Ok("Add Category") => add_category(config)?,
             Ok("Delete Project") => {
                 
                 if config.project_records.is_empty() {
@@ -107,7 +134,7 @@ pub fn projects_menu(config: &mut Config) -> Result<()>{
 
             Ok("Exit") => {
                 println!("Goodbye");
-                std::process::exit(0);
+                release_and_exit(0);
             },
             _ => continue,
         }
@@ -116,18 +143,98 @@ pub fn projects_menu(config: &mut Config) -> Result<()>{
 
 }
 
+/// Menu entry for the managed tag list
+pub fn tags_menu(config: &mut Config) -> Result<()> {
 
-pub fn reports_menu(config: &Config) -> Result<()>{
+    loop {
+        let options = vec!["List Tags", "Add Tag", "Delete Tag", "Back", "Exit"];
+        match Select::new("Tag Menu", options).prompt() {
+            Ok("List Tags") => list_tags(config),
+            Ok("Add Tag") => add_tag(config)?,
+            Ok("Delete Tag") => {
+
+                if config.settings.tags.is_empty() {
+                    println!("No tags to delete.");
+                    continue;
+                }
+
+                if let Ok(to_delete) = Select::new("Which tag do you want to delete? (Entries keep their tags)",
+                config.settings.tags.clone()).prompt() {
+                    config.delete_tag(&to_delete)?;
+                }
+            },
+            Ok("Back") => break Ok(()),
+            Ok("Exit") => {
+                println!("Goodbye");
+                release_and_exit(0);
+            },
+            _ => continue,
+        }
+    }
+}
+/// Menu entry for the user settings
+pub fn settings_menu(config: &mut Config) -> Result<()> {
+
+    loop {
+        let options = vec!["Same-Project Entries per Day", "CSV Format", "Timesheet Header", "Calendar Rules", "Undo Depth", "Git Sync", "Back", "Exit"];
+        match Select::new("Settings Menu", options).prompt() {
+            Ok("Same-Project Entries per Day") => choose_duplicate_policy(config)?,
+            Ok("CSV Format") => choose_csv_format(config)?,
+            Ok("Timesheet Header") => edit_timesheet_header(config)?,
+            Ok("Calendar Rules") => calendar_rules_menu(config)?,
+            Ok("Undo Depth") => edit_undo_depth(config)?,
+            Ok("Git Sync") => git_menu(config)?,
+            Ok("Back") => break Ok(()),
+            Ok("Exit") => {
+                println!("Goodbye");
+                release_and_exit(0);
+            },
+            _ => continue,
+        }
+    }
+}
+
+/// Menu entry for invoices, hourly rates and clients
+pub fn invoices_menu(config: &mut Config) -> Result<()> {
+
+    loop {
+        let options = vec!["Create Invoice", "Rates and Clients", "Invoice Settings", "Back", "Exit"];
+        match Select::new("Invoices Menu", options).prompt() {
+            Ok("Create Invoice") => create_invoice_menu(config)?,
+            Ok("Rates and Clients") => edit_rate_and_client(config)?,
+            Ok("Invoice Settings") => edit_invoice_settings(config)?,
+            Ok("Back") => break Ok(()),
+            Ok("Exit") => {
+                println!("Goodbye");
+                release_and_exit(0);
+            },
+            _ => continue,
+        }
+    }
+}
+
+pub fn reports_menu(config: &mut Config) -> Result<()>{
     base_report(config)?;
 
     loop {
-        let options = vec!["Monthly Report", "Back", "Exit"];
+        let options = vec!["Monthly Report", "Weekly Report", "Project Totals", "Tag Report", "Query", "Save Monthly Report", "Timesheet PDF", "Change History", "Locked Periods", "Export CSV", "Export XLSX", "Export Calendar", "Back", "Exit"];
         match Select::new("Reports menu", options).prompt() {
             Ok("Monthly Report") => monthly_report(config)?,
+            Ok("Weekly Report") => weekly_report(config)?,
+            Ok("Project Totals") => project_totals_report(config)?,
+            Ok("Query") => query_report(config)?,
+            Ok("Save Monthly Report") => render_monthly_menu(config)?,
+            Ok("Timesheet PDF") => timesheet_menu(config)?,
+            Ok("Change History") => history_menu(config)?,
+            Ok("Locked Periods") => locks_menu(config)?,
+            Ok("Export CSV") => export_csv_menu(config)?,
+            Ok("Export XLSX") => export_xlsx_menu(config)?,
+            Ok("Export Calendar") => export_ics_menu(config)?,
+            Ok("Tag Report") => tag_report(config)?,
             Ok("Back") => break Ok(()),
             Ok("Exit") => {
                 println!("Goodbye!");
-                std::process::exit(0);
+                release_and_exit(0);
             },
             _ => unreachable!()
         }       
@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime, SubsecRound};
+use serde::{Serialize, Deserialize};
+use anyhow::{Context, Result, bail};
+
+use crate::models::*;
+use crate::journal::{Change, Snapshot};
+use crate::history::record_revisions;
+
+/// File name of the undo history, next to the data files
+pub const UNDO_FILE: &str = "undo.json";
+
+/// One save that can be undone: the changes it made and the ones reverting them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoStep {
+    pub at: NaiveDateTime,
+    pub undo: Vec<Change>,
+    pub redo: Vec<Change>
+}
+
+impl UndoStep {
+    /// Short description of what the save changed, e.g. `workday 2025-11-09 added`
+    pub fn label(&self) -> String {
+        let redo: Vec<&Change> = self.redo.iter().filter(|c| c.is_shown()).collect();
+        let mut changes: Vec<String> = redo.iter().take(3).map(|c| c.to_string()).collect();
+        if redo.len() > 3 {
+            changes.push(format!("{} more", redo.len() - 3));
+        }
+        format!("{} ({})", changes.join(", "), self.at.format("%Y-%m-%d %H:%M"))
+    }
+}
+
+/// Saves that can be undone and undone ones that can be redone, newest last
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoStack {
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub undo: Vec<UndoStep>,
+    #[serde(default)]
+    pub redo: Vec<UndoStep>
+}
+
+impl UndoStack {
+    /// Loads the undo history next to the given data file. A missing file has none
+    pub fn open(data_path: &Path) -> Result<UndoStack> {
+        let path = data_path.with_file_name(UNDO_FILE);
+        let mut stack: UndoStack = match fs::read_to_string(&path) {
+            Ok(contents) if !contents.trim().is_empty() => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to deserialze undo history for path: {:#?}", path))?,
+            _ => UndoStack::default(),
+        };
+        stack.path = path;
+        Ok(stack)
+    }
+
+    /// Remembers a save, keeping at most `depth` steps. A new save can't be redone over
+    pub fn push(&mut self, undo: Vec<Change>, redo: Vec<Change>, depth: usize) {
+        self.undo.push(UndoStep { at: Local::now().naive_local().trunc_subsecs(0), undo, redo });
+        self.limit(depth);
+        self.redo.clear();
+    }
+
+    /// Keeps at most `depth` steps to undo. Without undo nothing can be redone either
+    pub fn limit(&mut self, depth: usize) {
+        let excess = self.undo.len().saturating_sub(depth);
+        self.undo.drain(..excess);
+        if depth == 0 {
+            self.redo.clear();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let json = serde_json::to_string(self).context("Failed to serialize undo history")?;
+        fs::write(&self.path, json).with_context(|| format!("Failed to write undo history to {:#?}", self.path))
+    }
+}
+
+impl Config {
+    /// Applies changes to the data, without saving. Workdays and entries keep their history
+    /// and get a revision with the reverted values, so an undo leaves a trace for audits
+    fn apply_changes(&mut self, changes: &[Change], reason: &str) {
+        let before = Snapshot::of(self);
+        let mut state = before.clone();
+        for change in changes {
+            change.apply(&mut state);
+        }
+        let entries: Vec<&ProjectEntry> = before.time_records.iter().flat_map(|r| r.project_entries.iter()).collect();
+        for record in state.time_records.iter_mut() {
+            if let Some(old) = before.time_records.iter().find(|r| r.id == record.id) {
+                record.history = old.history.clone();
+            }
+            for entry in record.project_entries.iter_mut() {
+                if let Some(old) = entries.iter().find(|e| e.id == entry.id) {
+                    entry.history = old.history.clone();
+                }
+            }
+        }
+        self.time_records = state.time_records;
+        self.project_records = state.project_records;
+        self.settings = state.settings;
+        record_revisions(&before, self, Some(reason.to_string()));
+    }
+
+    /// Error if undo is turned off with an undo depth of 0
+    pub fn check_undo_enabled(&self) -> Result<()> {
+        if self.settings.undo_depth == 0 {
+            bail!("Undo is turned off. Set an undo depth above 0 in the settings first");
+        }
+        Ok(())
+    }
+
+    /// Sets how many saves can be undone and drops the older steps. Writes the undo history
+    pub fn set_undo_depth(&mut self, depth: usize) -> Result<()> {
+        self.settings.undo_depth = depth;
+        self.undo.limit(depth);
+        self.undo.write()?;
+        self.save()
+    }
+
+    /// Reverts the last save.
+    /// Returns:
+    ///     What was undone, None if there is nothing to undo
+    pub fn undo(&mut self) -> Result<Option<String>> {
+        self.check_undo_enabled()?;
+        let Some(step) = self.undo.undo.pop() else {
+            return Ok(None);
+        };
+        self.apply_changes(&step.undo, "undone");
+        if let Err(e) = self.persist() {
+            self.undo.undo.push(step);
+            return Err(e);
+        }
+        let label = step.label();
+        self.undo.redo.push(step);
+        self.undo.write()?;
+        Ok(Some(label))
+    }
+
+    /// Repeats the last undone save.
+    /// Returns:
+    ///     What was redone, None if there is nothing to redo
+    pub fn redo(&mut self) -> Result<Option<String>> {
+        self.check_undo_enabled()?;
+        let Some(step) = self.undo.redo.pop() else {
+            return Ok(None);
+        };
+        self.apply_changes(&step.redo, "redone");
+        if let Err(e) = self.persist() {
+            self.undo.redo.push(step);
+            return Err(e);
+        }
+        let label = step.label();
+        self.undo.undo.push(step);
+        self.undo.write()?;
+        Ok(Some(label))
+    }
+}
+
+/// Menu entry to undo the last save
+pub fn undo_menu(config: &mut Config) -> Result<()> {
+    if let Err(e) = config.check_undo_enabled() {
+        println!("{}", e);
+        return Ok(());
+    }
+    match config.undo()? {
+        Some(label) => println!("Undone: {}", label),
+        None => println!("Nothing to undo"),
+    }
+    Ok(())
+}
+
+/// Menu entry to redo the last undone save
+pub fn redo_menu(config: &mut Config) -> Result<()> {
+    if let Err(e) = config.check_undo_enabled() {
+        println!("{}", e);
+        return Ok(());
+    }
+    match config.redo()? {
+        Some(label) => println!("Redone: {}", label),
+        None => println!("Nothing to redo"),
+    }
+    Ok(())
+}
+
+/// Menu entry to set how many saves can be undone
+pub fn edit_undo_depth(config: &mut Config) -> Result<()> {
+
+    println!("Currently {} saves can be undone", config.settings.undo_depth);
+    let Some(depth) = inquire::CustomType::<usize>::new("How many saves should be undoable?")
+        .with_default(config.settings.undo_depth)
+        .with_error_message("Please type in a whole number!")
+        .with_help_message("0 turns undo off")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    config.set_undo_depth(depth)
+}
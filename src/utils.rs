@@ -3,9 +3,13 @@ use std::vec;
 
 use chrono::{Datelike, Local, NaiveDate, NaiveTime, Weekday};
 use inquire::InquireError;
-use inquire::{CustomType, DateSelect, Select, Text, validator::Validation, Confirm};
+use inquire::{CustomType, DateSelect, MultiSelect, Select, Text, validator::Validation, Confirm};
 use crate::models::{TimeRecord, Project, ProjectEntry};
 use crate::models::*;
+use crate::query::{GroupBy, Query};
+use crate::reports::MonthlyReport;
+use crate::render::{OutputFormat, render_monthly};
+use crate::history::ask_reason;
 use anyhow::{Context, Result, anyhow};
 
 
@@ -66,9 +70,18 @@ pub fn record_time_record(config: &mut Config) -> Result<()> {
             Err(e) => return Err(e.into()),
         };
 
-    
+
+    if let Err(e) = config.check_unlocked(date) {
+        println!("{}", e);
+        return Ok(());
+    }
+
     // Check for existing record
     if let Some(existing) = config.time_records.iter_mut().find(|r| r.date == date){
+        if let Err(e) = existing.check_unbilled() {
+            println!("{}", e);
+            return Ok(());
+        }
         let confirm = match Confirm::new(&format!("A record for {} already exist. Do you want to override? (Y/n)", date))
             .prompt() {
                 Ok(confirm) => confirm,
@@ -105,14 +118,17 @@ pub fn record_time_record(config: &mut Config) -> Result<()> {
 
                 // Overwrite existing record
                 *existing = TimeRecord { 
+                    id: existing.id,
                     date,
                     start_time,
                     end_time,
                     pause_minutes,
-                    project_entries: vec![] // Overwrites existing project_entries!
+                    project_entries: vec![], // Overwrites existing project_entries!
+                    history: std::mem::take(&mut existing.history)
                 };
                 println!("Record Updated");
                 println!("You have worked {} hours today", existing.get_net_hours());
+                ask_reason(config)?;
                 config.save()?;
                 return Ok(());
         } else {
@@ -144,11 +160,13 @@ pub fn record_time_record(config: &mut Config) -> Result<()> {
         };
 
         let new_record = TimeRecord{
+            id: 0, // assigned by Config.add_time_record
             date,
             start_time,
             end_time,
             pause_minutes,
-            project_entries: vec![]
+            project_entries: vec![],
+            history: vec![]
         };
 
         config.add_time_record(&new_record)?;
@@ -185,12 +203,9 @@ pub fn pause_minutes() -> Result<Option<f64>> {
                 .with_help_message("Type something like 0.5, 0.75, 1 etc.")
                 .with_error_message("Please type in a valid number! (0.5, 0.75, 1.0)")
                 .with_validator(|input: &f64|{
-                    if (input * 4.0).fract() == 0.0 {
-                        Ok(Validation::Valid)
-                    } else {
-                        Ok(Validation::Invalid(
-                            "Pause must be in 0.25 hour increments".into()
-                        ))
+                    match TimeRecord::validate_pause(*input) {
+                        Ok(()) => Ok(Validation::Valid),
+                        Err(msg) => Ok(Validation::Invalid(msg.into())),
                     }
                 }).prompt_skippable()?;
 
@@ -226,6 +241,11 @@ pub fn record_project_work(config: &mut Config) -> Result<()>{
             },
             Err(e) => return Err(e)
         };
+
+        if let Err(e) = config.check_unlocked(time_record_ans) {
+            println!("{}", e);
+            return Ok(());
+        }
         
         let proj_ans = match choose_project(&config.project_records, "For what project would you like to record an activity?"){
             Ok(Some(project_entry)) => project_entry,
@@ -244,6 +264,12 @@ pub fn record_project_work(config: &mut Config) -> Result<()>{
             }
         };
 
+        if config.settings.duplicate_policy == DuplicatePolicy::Reject
+            && has_entry_for_project(&config.time_records, &time_record_ans, &single_proj.code) {
+            println!("There is already an entry for {} on {}. Please edit that entry instead.", single_proj.code, time_record_ans);
+            return Ok(());
+        }
+
         // Retrieve the already assigned hous for the workday
         let assigned_hours = match get_activity_hours(&time_record_ans, &config.time_records) {
             Ok(Some(assigned_hours)) => assigned_hours,
@@ -256,10 +282,9 @@ pub fn record_project_work(config: &mut Config) -> Result<()>{
 
         // Ask the user for his activities in the poject
         let activity = match Text::new("What did you do?:").with_validator(|input: &str| {
-            if input.len() <= 500 {
-                Ok(Validation::Valid)
-            } else {
-                Ok(Validation::Invalid("Entry must be no longer than 500 characters!".into()))
+            match ProjectEntry::validate_activity(input) {
+                Ok(()) => Ok(Validation::Valid),
+                Err(msg) => Ok(Validation::Invalid(msg.into())),
             }
             }).prompt() {
 
@@ -271,11 +296,23 @@ pub fn record_project_work(config: &mut Config) -> Result<()>{
 
             Err(e) => return Err(e.into()),
         };
+        let tags = match choose_tags(&mut config.settings, &activity, &[])? {
+            Some(tags) => tags,
+            None => {
+                println!("Operation cancelled, returning to menu...");
+                return Ok(());
+            }
+        };
+
         // Initialize ProjectEntry
         let new_project_entry = ProjectEntry {
+            id: 0, // assigned by Config.add_project_entry
             project_name: single_proj, 
             hours: assigned_hours,
-            activity: activity
+            activity: activity,
+            tags,
+            invoice: None,
+            history: vec![]
         };
 
         config.add_project_entry(time_record_ans, new_project_entry)?;
@@ -284,15 +321,24 @@ pub fn record_project_work(config: &mut Config) -> Result<()>{
 
 }
 
+/// Checks if the record of a date already has an entry for a project
+fn has_entry_for_project(time_records: &[TimeRecord], date: &NaiveDate, code: &str) -> bool {
+    time_records
+        .iter()
+        .find(|r| r.date == *date)
+        .is_some_and(|r| r.prohibit_duplicate_entry(code))
+}
+
 /// Function to prompt the user for the remaining time. 
 /// Adding the date entry and the project, this function will list possible working hours, so that you do not exceed the days amount of work 
 pub fn get_activity_hours(date: &NaiveDate, time_record: &Vec<TimeRecord>) -> Result<Option<f64>> {
 
 
-    let remaining_hours = time_record
+    let record = time_record
     .iter()
-    .find(|r| r.date == *date).map(|r| r.remaining_hours())
+    .find(|r| r.date == *date)
     .expect("No Hours found for this date!");
+    let remaining_hours = record.remaining_hours();
 
     if remaining_hours == 0.0 {
         println!("No hours left to record :(");
@@ -317,15 +363,13 @@ pub fn get_activity_hours(date: &NaiveDate, time_record: &Vec<TimeRecord>) -> Re
             Err(e) => return Err(e.into()),
         };
 
-        if assigned_hours == 0.0 {
-            println!("Please assigne more than zero!");
-            continue;
-        } else if assigned_hours <= remaining_hours {
-            break assigned_hours;
-        } else {
-            println!("You have assigned to much time. Please try again");
-            continue;
-        }; 
+        match record.validate_hours(assigned_hours) {
+            Ok(()) => break assigned_hours,
+            Err(msg) => {
+                println!("{}", msg);
+                continue;
+            }
+        };
         
     };
     
@@ -374,7 +418,7 @@ pub fn add_project(config: &mut Config)-> Result<()>{
 
 
         if !config.project_records.iter().any(|p| p.code == code) {
-            config.add_project(Project { code, allocation })?;
+            config.add_project(Project { code, allocation, internal: false, rate: None, client: None })?;
             //projects.push(Project { code, allocation });
             //save_data(&projects, "projects.json");
             //println!("Projct Added");
@@ -389,18 +433,114 @@ pub fn add_project(config: &mut Config)-> Result<()>{
 
 }
 
-/// Searchs for a project code in an &[Project]
+/// Asks the user for a non-project category like admin, training or vacation.
+/// Categories are stored with the projects but flagged as internal (non-billable)
+pub fn add_category(config: &mut Config) -> Result<()> {
+
+    loop {
+        let code = Text::new("Enter a category name: ").with_validator(|input: &str| {
+            if input.is_empty() {
+                Ok(Validation::Invalid("Category name can not be empty!".into()))
+            } else if input.len() <= 12 {
+                Ok(Validation::Valid)
+            } else {
+                Ok(Validation::Invalid("Category name can only be 12 characters long!".into()))
+            }
+        }).prompt_skippable()?;
+
+        let Some(code) = code else {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(());
+        };
+
+        if config.project_records.iter().any(|p| p.code == code) {
+            println!("A project or category with this code already exists");
+            continue;
+        }
+
+        config.add_project(Project::category(&code))?;
+        break Ok(());
+    }
+}
+
+/// Lets the user pick tags for a project entry from the managed tag list.
+/// `#tags` written in the activity are added to the list and preselected, as are the `current` tags
 /// Returns:
-///     Option<Project>
-pub fn find_project(projects: &[Project], code: &str) -> Option<Project> {
-    if code == "INT" {
-        return Some(Project{code: String::from("INT"), allocation: 0.0});
+///     None if the user cancelled
+pub fn choose_tags(settings: &mut Settings, activity: &str, current: &[String]) -> Result<Option<Vec<String>>> {
+
+    let mut preselected = ProjectEntry::extract_tags(activity);
+    for tag in current {
+        if !preselected.contains(tag) {
+            preselected.push(tag.clone());
+        }
     }
+    settings.add_tags(&preselected);
 
-    if code == "Vacation" {
-        return Some(Project { code: String::from("Vacation"), allocation: 0.0 });
+    if settings.tags.is_empty() {
+        return Ok(Some(preselected));
     }
 
+    let defaults: Vec<usize> = settings.tags
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| preselected.contains(t))
+        .map(|(i, _)| i)
+        .collect();
+
+    let selection = MultiSelect::new("Which tags apply to this activity?", settings.tags.clone())
+        .with_default(&defaults)
+        .with_help_message("Space to toggle, enter to confirm. Type #tag in the activity to create new tags")
+        .prompt_skippable()?;
+
+    Ok(selection)
+}
+
+/// Asks the user for a new tag for the managed tag list
+pub fn add_tag(config: &mut Config) -> Result<()> {
+
+    let tag = Text::new("Enter a tag: ")
+        .with_help_message("e.g. meeting, development, review")
+        .with_validator(|input: &str| {
+            let tag = input.trim().trim_start_matches('#');
+            if tag.is_empty() || tag.contains(char::is_whitespace) {
+                Ok(Validation::Invalid("A tag is a single word!".into()))
+            } else {
+                Ok(Validation::Valid)
+            }
+        }).prompt_skippable()?;
+
+    let Some(tag) = tag else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if config.settings.tags.contains(&tag) {
+        println!("Tag already exists");
+        return Ok(());
+    }
+
+    config.settings.add_tags(&[tag]);
+    config.save()?;
+    println!("Tag added");
+    Ok(())
+}
+
+/// Lists the managed tags
+pub fn list_tags(config: &Config) {
+    if config.settings.tags.is_empty() {
+        println!("Currently no stored tags");
+    }
+    for tag in &config.settings.tags {
+        println!("#{}", tag);
+    }
+}
+
+/// Searchs for a project code in an &[Project]
+/// Returns:
+///     Option<Project>
+pub fn find_project(projects: &[Project], code: &str) -> Option<Project> {
     projects.iter().find(|p| p.code == code).cloned()
 }
 
@@ -409,120 +549,192 @@ pub fn clear_screen() {
     print!("{esc}c", esc = 27 as char);
 }
 
-/// Edit the Project Entries in a TimeRecord
-pub fn edit_workday_record(config: &mut Config) -> Result<()> {    
+/// Prints the project entries of a record
+pub fn print_project_entries(record: &TimeRecord) {
+    for pe in &record.project_entries{
+        print_project_entry(pe);
+        println!("\n");
+    }
+}
+
+/// Prints a single project entry
+pub fn print_project_entry(pe: &ProjectEntry) {
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+    println!("Entry: #{}", pe.id);
+    println!("Project: {}", pe.project_name.code);
+    println!("Assigned hours this day: {}", pe.hours);
+    println!("Activity: {}", pe.activity);
+    if !pe.tags.is_empty() {
+        println!("Tags: {}", pe.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" "));
+    }
+    if let Some(invoice) = &pe.invoice {
+        println!("Billed with invoice {}", invoice);
+    }
+    println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
+}
+
+/// Prints a workday with its times and entries
+pub fn print_workday(record: &TimeRecord) {
+    println!("Workday #{} on {}: {} - {}, pause {} h, {} h net, {} h unassigned",
+        record.id, record.date, record.start_time.format("%H:%M"), record.end_time.format("%H:%M"),
+        record.pause_minutes, record.get_net_hours(), record.remaining_hours());
+    print_project_entries(record);
+}
 
-    let (selected_date, _) = match choose_date(config, "For what date would you like to edit the entries?") {
+/// Lets the user choose a date and one of its project entries
+/// Returns:
+///     The id of the chosen ProjectEntry, None if cancelled or the day has no entries
+pub fn choose_project_entry(config: &Config, prompt: &str) -> Result<Option<u64>> {
+
+    let (selected_date, record) = match choose_date(config, prompt) {
         Ok(Some(value)) => value,
-        Ok(None) => {
-            println!("Operation cancelled. Returning to main...");
-            return Ok(());
-        },
+        Ok(None) => return Ok(None),
         Err(e) => return Err(e)
     };
 
-    if let Some(record) = config.time_records.iter_mut().find(|r| r.date == selected_date) {
-        /* Could be written as:
-            let record = config
-                .time_records
-                .iter_mut()
-                .find(|r| r.date == selected_date)
-                .ok_or_else(
-                    || println!("An Error Occured: {}", selected_date)
-                );  
-         */
-
-        if record.project_entries.is_empty() {
-            println!("Currently no project records for {}. Please enter a record first!\n", selected_date);
+    if record.project_entries.is_empty() {
+        println!("Currently no project records for {}. Please enter a record first!\n", selected_date);
+        return Ok(None);
+    }
+
+    // Print the user, what he has allocated and what he has done in a project
+    println!("These are the records for the selected date: {}", selected_date);
+    println!("\n");
+    print_project_entries(&record);
+
+    let labels: Vec<String> = record.project_entries.iter().map(|e| e.label()).collect();
+
+    let selected = match Select::new("Which Project entry?", labels.clone()).prompt() {
+        Ok(value) => value,
+        Err(InquireError::OperationCanceled) | Err(InquireError::OperationInterrupted) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    // Labels start with the unique id, so the position maps back to the entry
+    let index = labels.iter().position(|l| *l == selected).context("Selected entry not found")?;
+    Ok(Some(record.project_entries[index].id))
+}
+
+/// Edit the Project Entries in a TimeRecord
+pub fn edit_workday_record(config: &mut Config) -> Result<()> {    
+
+    match choose_project_entry(config, "For what date would you like to edit the entries?")? {
+        Some(entry_id) => edit_project_entry(config, entry_id),
+        None => {
             println!("Returning to main...");
-            return Ok(());
+            Ok(())
         }
+    }
+}
 
-         // Print the user, what he has allocated and what he has done in a project
-         println!("These are the records for the selected date: {}", selected_date);
-         println!("\n");
+/// Delete a single Project Entry of a TimeRecord
+pub fn delete_workday_entry(config: &mut Config) -> Result<()> {
 
-         for pe in &record.project_entries{
-            println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-            println!("Project: {}", pe.project_name.code);
-            println!("Assigned hours this day: {}", pe.hours);
-            println!("Activity: {}", pe.activity);
-            println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-            println!("\n");
-         }
+    let Some(entry_id) = choose_project_entry(config, "For what date would you like to delete an entry?")? else {
+        println!("Returning to main...");
+        return Ok(());
+    };
 
-         let vec_pcodes: Vec<String> = record.project_entries
-            .iter()
-            .map(|p|p.project_name.code.clone())
-            .collect();
+    let confirm = Confirm::new(&format!("Are you sure you want to delete entry #{}?", entry_id))
+        .prompt_skippable()?;
 
-         let select_pcode = match Select::new("Which Project entry would you like to edit?",
-            vec_pcodes).prompt() {
-                Ok(value) => value,
-                Err(InquireError::OperationCanceled) | Err(InquireError::OperationInterrupted) => {
-                    println!("Operation cancelled. Returning to main...");
-                    return Ok(());
-                },
-                Err(e) => return Err(e.into()),
-            };
+    if confirm == Some(true) {
+        ask_reason(config)?;
+        config.delete_project_entry(entry_id)?;
+    } else {
+        println!("Entry NOT deleted");
+    }
+    Ok(())
+}
 
-        
-        if let Some(project) = record.project_entries
-        // s.o.
-            .iter_mut()
-            .find(|p| p.project_name.code == select_pcode){
-
-
-            let assigned_hours = CustomType::<f64>::new("How many hours would you like to assign?")
-                .with_default(project.hours)
-                .with_error_message("Please type in a valid number")
-                .with_help_message("Valid format are 0.5, 1.0, 3.5 etc")
-                .prompt_skippable()
-                .with_context(|| format!("Failed to assign hours for Project: {:#?}", project.project_name.code))?;
-
-            let assigned_hours = match assigned_hours {
-                Some(hours) => hours,
-                None => {
-                    println!("Operation cancelled. Returning to main...");
-                    return Ok(())
-                }
-            };
+/// Edit hours, activity and tags of a ProjectEntry identified by its id
+pub fn edit_project_entry(config: &mut Config, entry_id: u64) -> Result<()> {
 
-            let activity = Text::new("What did you do?")
-                .with_default(&project.activity)
-                .with_validator(|input: &str| {
-                    if input.len() <= 500 {
-                        Ok(Validation::Valid)
-                    } else {
-                        Ok(Validation::Invalid("Activity should be no longer than 500 characters!".into()))
-                    }
-                })
-                .prompt_skippable()
-                .with_context(|| format!("Failed to assign activity for Project: {:#?}",project.project_name.code))?;
-
-            let activity = match activity {
-                Some(activity) => activity,
-                None => {
-                    println!("Operation cancelled. Returning to main...");
-                    return Ok(());
-                }
-            };
+    let date = config.time_records.iter().find(|r| r.project_entries.iter().any(|e| e.id == entry_id)).map(|r| r.date);
+    if let Some(Err(e)) = date.map(|d| config.check_unlocked(d)) {
+        println!("{}", e);
+        return Ok(());
+    }
+
+    let Some((record, project)) = config.find_entry(entry_id) else {
+        return Err(anyhow!("No project entry with id #{}", entry_id));
+    };
+
+    if let Some(invoice) = &project.invoice {
+        println!("Entry #{} is billed with invoice {} and can't be changed anymore", entry_id, invoice);
+        return Ok(());
+    }
+
+    // The day without this entry, so its own hours count as free
+    let project = project.clone();
+    let mut others = record.clone();
+    others.project_entries.retain(|e| e.id != entry_id);
+
+    let assigned_hours = CustomType::<f64>::new("How many hours would you like to assign?")
+        .with_default(project.hours)
+        .with_error_message("Please type in a valid number")
+        .with_help_message("Valid format are 0.5, 1.0, 3.5 etc")
+        .with_validator(move |input: &f64| match others.validate_hours(*input) {
+            Ok(()) => Ok(Validation::Valid),
+            Err(msg) => Ok(Validation::Invalid(msg.into())),
+        })
+        .prompt_skippable()
+        .with_context(|| format!("Failed to assign hours for Project: {:#?}", project.project_name.code))?;
 
-            // Todo: Overwrite Entry with the new information!
+    let assigned_hours = match assigned_hours {
+        Some(hours) => hours,
+        None => {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(())
+        }
+    };
 
-            project.hours = assigned_hours;
-            project.activity = activity;
+    let activity = Text::new("What did you do?")
+        .with_default(&project.activity)
+        .with_validator(|input: &str| {
+            match ProjectEntry::validate_activity(input) {
+                Ok(()) => Ok(Validation::Valid),
+                Err(msg) => Ok(Validation::Invalid(msg.into())),
+            }
+        })
+        .prompt_skippable()
+        .with_context(|| format!("Failed to assign activity for Project: {:#?}",project.project_name.code))?;
 
-            config.save()?;
+    let activity = match activity {
+        Some(activity) => activity,
+        None => {
+            println!("Operation cancelled. Returning to main...");
             return Ok(());
-        } else {
-        println!("Something wen't wrong! No record found for project: {:#?}", select_pcode);
-        Err(anyhow!("Exiting program! Check you code!"))
         }
-    } else {
-        println!("Something wen't wrong! No record found for date: {:#?}", selected_date);
-        Err(anyhow!("Exiting program! Check you code!"))
-    }
+    };
+
+    let tags = match choose_tags(&mut config.settings, &activity, &project.tags)? {
+        Some(tags) => tags,
+        None => {
+            println!("Operation cancelled. Returning to main...");
+            return Ok(());
+        }
+    };
+
+    ask_reason(config)?;
+    config.update_project_entry(entry_id, Some(assigned_hours), Some(activity), Some(tags))
+}
+
+/// Lets the user change the policy for several entries of the same project on one day
+pub fn choose_duplicate_policy(config: &mut Config) -> Result<()> {
+
+    println!("Current policy: {}", config.settings.duplicate_policy);
+
+    let options = vec![DuplicatePolicy::Allow, DuplicatePolicy::Merge, DuplicatePolicy::Reject];
+    let Some(policy) = Select::new("What should happen with a second entry for the same project on a day?", options)
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    config.settings.duplicate_policy = policy;
+    config.save()?;
+    Ok(())
 }
 
 /// Call function to prompt the user a list of the currenty stored workdays
@@ -554,6 +766,7 @@ pub fn choose_date(config: &Config, prompt: &str) -> Result<Option<(NaiveDate, T
 }
 
 /// Quite literaly lists all the projects given in a &[Project] type
+/// Internal categories are listed separately
 pub fn list_projects(config: &Config) -> Result<()> {
 
     
@@ -561,8 +774,18 @@ pub fn list_projects(config: &Config) -> Result<()> {
         println!("Currently no stored projects");
         Ok(())
     } else {
-        for p in config.project_records.clone() {
-            println!("{}", p.code);
+        println!("Projects:");
+        for p in config.projects() {
+            match (&p.client, p.rate) {
+                (Some(client), Some(rate)) => println!("  {} ({}, {:.2}/h)", p.code, client, rate),
+                (Some(client), None) => println!("  {} ({})", p.code, client),
+                (None, Some(rate)) => println!("  {} ({:.2}/h)", p.code, rate),
+                (None, None) => println!("  {}", p.code),
+            }
+        }
+        println!("Internal categories (non-billable):");
+        for c in config.categories() {
+            println!("  {}", c.code);
         }
         
         Ok(())
@@ -570,6 +793,7 @@ pub fn list_projects(config: &Config) -> Result<()> {
 }
 
 /// Iterates over all stored projects. The user can thus freely choose, for what code he want to add an activity
+/// Customer projects are listed first, internal categories after them
 pub fn choose_project(projects: &[Project], prompt: &str) -> Result<Option<String>> {
 
     if projects.is_empty() {
@@ -577,10 +801,10 @@ pub fn choose_project(projects: &[Project], prompt: &str) -> Result<Option<Strin
         return Err(anyhow!("No stored projects"));
     }
 
-    let mut vec_of_strings: Vec<String> = projects.iter().map(|p| p.code.clone()).collect();
-
-    vec_of_strings.push(String::from("INT"));
-    vec_of_strings.push(String::from("Vacation"));
+    let vec_of_strings: Vec<String> = projects.iter().filter(|p| !p.internal)
+        .chain(projects.iter().filter(|p| p.internal))
+        .map(|p| p.code.clone())
+        .collect();
 
 
     let proj_entry = match Select::new(prompt, vec_of_strings).prompt(){
@@ -599,42 +823,30 @@ pub fn base_report(config: &Config) -> Result<()> {
 
     let date = NaiveDate::from(Local::now().date_naive());
 
-    let totals = filter_time_record_totals(config, date.year(), date.month())?;
+    let report = MonthlyReport::build(config, date.year(), date.month())?;
 
     // Print the report
-    println!("Hour for {}/{}", date.month(), date.year());
-    for (project, hours) in totals {
-        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-        println!("Project: {}", project);
-        println!("Assigned hours this month: {}", hours);
-        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-        println!("\n");
-    };
+    print_report(&report);
 
     return Ok(());
 }
 
 /// Function to iterate over a given month a return every entry for a project and the hours
 /// assigned for this project
-fn filter_time_record_totals(config: &Config, year: i32, month: u32) -> Result<HashMap<String,f64>> {
-
-    let mut totals = HashMap::new();
+pub fn filter_time_record_totals(config: &Config, year: i32, month: u32) -> Result<HashMap<String,f64>> {
 
-    let date = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let range = DateRange::month(year, month)
+        .with_context(|| format!("Invalid month {}/{}", month, year))?;
 
-    for record in config.time_records.iter().filter(|r| {
-        r.date.month() == date.month() && r.date.year() == date.year()
-    }) {
-        for p_entry in &record.project_entries {
-            *totals.entry(p_entry.project_name.code.clone()).or_insert(0.0) += p_entry.hours
-        }
-    };
+    let totals = Query::for_range(range)
+        .grouped_by(GroupBy::Project)
+        .totals(config);
 
-    return Ok(totals);
+    return Ok(totals.into_iter().collect());
 }
 
 /// Function to receive user input for a year he wants to have infomration for
-fn choose_year(config: &Config, prompt: &str) -> Result<Option<i32>> {
+pub fn choose_year(config: &Config, prompt: &str) -> Result<Option<i32>> {
 
     let mut years_in_storage = BTreeSet::new();
     
@@ -662,7 +874,7 @@ fn choose_year(config: &Config, prompt: &str) -> Result<Option<i32>> {
 
 /// Function to receive user input for a month he want the information for
 /// year should only 
-fn choose_month(config: &Config, prompt: &str, year: i32) -> Result<Option<MonthChoice>> {
+pub fn choose_month(config: &Config, prompt: &str, year: i32) -> Result<Option<MonthChoice>> {
 
     // Consider using a struct instead, holding month name and numer.
     let mut month_in_storage = BTreeSet::new();
@@ -705,19 +917,24 @@ fn choose_month(config: &Config, prompt: &str, year: i32) -> Result<Option<Month
 
 }
 
-fn print_report(totals: HashMap<String,f64>, date: &NaiveDate) -> Result<()> {
+/// Project code with its assigned hours
+pub type ProjectHours = (String, f64);
 
-    println!("Hour for {}/{}", date.month(), date.year());
-    for (project, hours) in totals {
-        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-        println!("Project: {}", project);
-        println!("Assigned hours this month: {}", hours);
-        println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++");
-        println!("\n");
-    };
-
-    Ok(())
+/// Splits the totals of a month into customer projects and internal categories
+/// Returns:
+///     (projects, internal), both sorted by code
+pub fn split_internal_totals(config: &Config, totals: HashMap<String,f64>) -> (Vec<ProjectHours>, Vec<ProjectHours>) {
+    let (mut internal, mut projects): (Vec<_>, Vec<_>) = totals
+        .into_iter()
+        .partition(|(code, _)| config.is_internal(code));
+
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+    internal.sort_by(|a, b| a.0.cmp(&b.0));
+    (projects, internal)
+}
 
+fn print_report(report: &MonthlyReport) {
+    print!("{}", render_monthly(report, OutputFormat::Text));
 }
 
 pub fn monthly_report(config: &Config) -> Result<()> {
@@ -743,11 +960,9 @@ pub fn monthly_report(config: &Config) -> Result<()> {
         Err(e) => return Err(e),
     };
 
-    let totals = filter_time_record_totals(config, year_choice, month_choice.month_number)?;
+    let report = MonthlyReport::build(config, year_choice, month_choice.month_number)?;
 
-    print_report(totals, 
-        &NaiveDate::from_ymd_opt(year_choice, month_choice.month_number, 1).unwrap()
-    )?;
+    print_report(&report);
 
     return Ok(());
 }
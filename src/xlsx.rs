@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use chrono::NaiveDate;
+use inquire::Text;
+use rust_xlsxwriter::{Format, Formula, Workbook, Worksheet, cell_range};
+use anyhow::{Context, Result};
+
+use crate::models::*;
+use crate::query::Query;
+use crate::reports::choose_date_range;
+use crate::export::write_bytes;
+
+/// Cell formats shared by the sheets
+struct Formats {
+    header: Format,
+    date: Format,
+    time: Format,
+    hours: Format,
+    total: Format,
+    total_hours: Format
+}
+
+impl Formats {
+    fn new() -> Formats {
+        Formats {
+            header: Format::new().set_bold(),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            time: Format::new().set_num_format("hh:mm"),
+            hours: Format::new().set_num_format("0.00"),
+            total: Format::new().set_bold(),
+            total_hours: Format::new().set_bold().set_num_format("0.00"),
+        }
+    }
+}
+
+fn write_header(sheet: &mut Worksheet, columns: &[&str], formats: &Formats) -> Result<()> {
+    for (col, title) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, &formats.header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+/// Writes a bold SUM over the rows of a column, with the value cached for viewers that do not calculate
+fn write_sum(sheet: &mut Worksheet, row: u32, col: u16, first_row: u32, total: f64, formats: &Formats) -> Result<()> {
+    if row == first_row {
+        sheet.write_number_with_format(row, col, 0.0, &formats.total_hours)?;
+        return Ok(());
+    }
+    let formula = Formula::new(format!("=SUM({})", cell_range(first_row, col, row - 1, col)))
+        .set_result(format!("{}", total));
+    sheet.write_formula_with_format(row, col, formula, &formats.total_hours)?;
+    Ok(())
+}
+
+/// Sheet with one row per workday
+fn workdays_sheet(sheet: &mut Worksheet, records: &[&TimeRecord], formats: &Formats) -> Result<()> {
+    sheet.set_name("Workdays")?;
+    write_header(sheet, &["Date", "Start", "End", "Pause (h)", "Net hours", "Allocated"], formats)?;
+
+    let mut row = 1;
+    for record in records {
+        sheet.write_datetime_with_format(row, 0, record.date, &formats.date)?;
+        sheet.write_datetime_with_format(row, 1, record.start_time, &formats.time)?;
+        sheet.write_datetime_with_format(row, 2, record.end_time, &formats.time)?;
+        sheet.write_number_with_format(row, 3, record.pause_minutes, &formats.hours)?;
+        sheet.write_number_with_format(row, 4, record.get_net_hours(), &formats.hours)?;
+        sheet.write_number_with_format(row, 5, record.allocated_hours(), &formats.hours)?;
+        row += 1;
+    }
+
+    sheet.write_string_with_format(row, 0, "Total", &formats.total)?;
+    write_sum(sheet, row, 4, 1, records.iter().map(|r| r.get_net_hours()).sum(), formats)?;
+    write_sum(sheet, row, 5, 1, records.iter().map(|r| r.allocated_hours()).sum(), formats)?;
+    sheet.set_column_width(0, 12)?;
+    sheet.set_column_width(3, 10)?;
+    Ok(())
+}
+
+/// Sheet with one row per project entry
+fn entries_sheet(sheet: &mut Worksheet, config: &Config, range: DateRange, formats: &Formats) -> Result<()> {
+    sheet.set_name("Entries")?;
+    write_header(sheet, &["Date", "Entry", "Project", "Internal", "Hours", "Activity", "Tags"], formats)?;
+
+    let hits = Query::for_range(range).entries(config);
+    let mut row = 1;
+    for hit in &hits {
+        let code = &hit.entry.project_name.code;
+        sheet.write_datetime_with_format(row, 0, hit.record.date, &formats.date)?;
+        sheet.write_number(row, 1, hit.entry.id as f64)?;
+        sheet.write_string(row, 2, code)?;
+        sheet.write_string(row, 3, if config.is_internal(code) { "yes" } else { "no" })?;
+        sheet.write_number_with_format(row, 4, hit.entry.hours, &formats.hours)?;
+        sheet.write_string(row, 5, &hit.entry.activity)?;
+        sheet.write_string(row, 6, hit.entry.tags.join(" "))?;
+        row += 1;
+    }
+
+    sheet.write_string_with_format(row, 0, "Total", &formats.total)?;
+    write_sum(sheet, row, 4, 1, hits.iter().map(|h| h.entry.hours).sum(), formats)?;
+    sheet.set_column_width(0, 12)?;
+    sheet.set_column_width(2, 14)?;
+    sheet.set_column_width(5, 60)?;
+    sheet.set_column_width(6, 20)?;
+    Ok(())
+}
+
+/// Sheet with projects as rows and the days with entries as columns, totals per row and column
+fn pivot_sheet(sheet: &mut Worksheet, config: &Config, range: DateRange, formats: &Formats) -> Result<()> {
+    sheet.set_name("Pivot")?;
+
+    let mut hours: BTreeMap<(String, NaiveDate), f64> = BTreeMap::new();
+    for hit in Query::for_range(range).entries(config) {
+        *hours.entry((hit.entry.project_name.code.clone(), hit.record.date)).or_insert(0.0) += hit.entry.hours;
+    }
+    let projects: BTreeSet<&String> = hours.keys().map(|(code, _)| code).collect();
+    let days: BTreeSet<NaiveDate> = hours.keys().map(|(_, date)| *date).collect();
+
+    sheet.write_string_with_format(0, 0, "Project", &formats.header)?;
+    for (i, day) in days.iter().enumerate() {
+        sheet.write_datetime_with_format(0, i as u16 + 1, day, &formats.header.clone().set_num_format("dd.mm."))?;
+    }
+    let total_col = days.len() as u16 + 1;
+    sheet.write_string_with_format(0, total_col, "Total", &formats.header)?;
+
+    let mut row = 1;
+    for code in &projects {
+        sheet.write_string(row, 0, *code)?;
+        let mut project_total = 0.0;
+        for (i, day) in days.iter().enumerate() {
+            if let Some(h) = hours.get(&((*code).clone(), *day)) {
+                sheet.write_number_with_format(row, i as u16 + 1, *h, &formats.hours)?;
+                project_total += h;
+            }
+        }
+        let formula = Formula::new(format!("=SUM({})", cell_range(row, 1, row, total_col - 1)))
+            .set_result(format!("{}", project_total));
+        sheet.write_formula_with_format(row, total_col, formula, &formats.total_hours)?;
+        row += 1;
+    }
+
+    sheet.write_string_with_format(row, 0, "Total", &formats.total)?;
+    for (i, day) in days.iter().enumerate() {
+        let day_total = hours.iter().filter(|((_, d), _)| d == day).map(|(_, h)| h).sum();
+        write_sum(sheet, row, i as u16 + 1, 1, day_total, formats)?;
+    }
+    write_sum(sheet, row, total_col, 1, hours.values().sum(), formats)?;
+    sheet.set_column_width(0, 14)?;
+    sheet.set_freeze_panes(1, 1)?;
+    Ok(())
+}
+
+/// Builds the workbook of a date range with the sheets Workdays, Entries and Pivot
+pub fn range_xlsx(config: &Config, range: DateRange) -> Result<Vec<u8>> {
+    let mut records: Vec<&TimeRecord> = config.time_records.iter().filter(|r| range.contains(r.date)).collect();
+    records.sort_by_key(|r| r.date);
+
+    let formats = Formats::new();
+    let mut workbook = Workbook::new();
+    workdays_sheet(workbook.add_worksheet(), &records, &formats)?;
+    entries_sheet(workbook.add_worksheet(), config, range, &formats)?;
+    pivot_sheet(workbook.add_worksheet(), config, range, &formats)?;
+    workbook.save_to_buffer().context("Failed to create the XLSX file")
+}
+
+/// Menu entry to export a date range as Excel workbook
+pub fn export_xlsx_menu(config: &Config) -> Result<()> {
+
+    let Some(range) = choose_date_range(config)? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    let Some(path) = Text::new("File name:")
+        .with_default("export.xlsx")
+        .prompt_skippable()? else {
+        println!("Operation cancelled. Returning to main...");
+        return Ok(());
+    };
+
+    write_bytes(&range_xlsx(config, range)?, Path::new(&path))
+}